@@ -1,5 +1,50 @@
 use core::fmt;
 use grid::Grid;
+use std::str::FromStr;
+
+mod bfs;
+mod bitgrid;
+pub mod blocks;
+mod compress;
+pub mod cycle;
+mod diff;
+pub mod geometry;
+mod graph;
+mod grid_like;
+mod image;
+mod layered;
+mod pattern;
+mod record;
+mod reflect;
+mod regions;
+mod render;
+pub mod search;
+mod sparse;
+mod tiling;
+mod tilt;
+mod tokens;
+mod transform;
+mod walk;
+
+pub use bfs::{Connectivity, DistanceMap};
+pub use bitgrid::BitGrid;
+pub use blocks::ParseGridError;
+pub use compress::Compression;
+pub use diff::{Change, ChangedCells, GridDiff};
+pub use graph::{Edge, GridGraph, NodeId};
+pub use grid_like::{GridLike, Heading, Neighbourhood, PosMap, PosSet};
+pub use image::{Image, Rgb, Svg};
+pub use layered::{LayerId, LayeredGrid, LayeredRenderer};
+pub use pattern::{PatternMatch, WordMatch};
+pub use record::Recorder;
+pub use reflect::{Axis, Reflection};
+pub use regions::{Region, RegionId, Regions};
+pub use render::{Renderable, Renderer};
+pub use sparse::{SignedCoord, SparseGrid};
+pub use tiling::{TiledView, WrappingView};
+pub use tokens::{Lines, Token};
+pub use transform::{GridView, GridViewMut, Transform};
+pub use walk::{Until, Walk};
 
 // This module extends the functionality of the grid crate with some functions I often use when working with grids
 
@@ -8,9 +53,82 @@ pub trait GridExt<T> {
     where
         Self: Sized,
         T: From<char>;
+    fn parse_blocks(input: &str) -> Result<Vec<Self>, ParseGridError>
+    where
+        Self: Sized,
+        T: TryFrom<char>;
     fn get_at_coord(&self, coord: &Coord) -> Option<&T>;
     fn get_at_coord_mut(&mut self, coord: &Coord) -> Option<&mut T>;
     fn relative_coord(&self, coord: &Coord, dir: &Direction) -> Option<Coord>;
+    fn line(&self, from: &Coord, to: &Coord) -> Option<Walk<'_, Grid<T>>>;
+    fn row_iter(&self, row: usize, dir: &Direction) -> Walk<'_, Grid<T>>;
+    fn col_iter(&self, col: usize, dir: &Direction) -> Walk<'_, Grid<T>>;
+    fn regions<F>(&self, same_region: F) -> Regions
+    where
+        F: FnMut(&T, &T) -> bool;
+    fn wrapping(&self) -> WrappingView<'_, T>;
+    fn tiled(&self) -> TiledView<'_, T>;
+    fn view(&self, transform: Transform) -> GridView<'_, T>;
+    fn view_mut(&mut self, transform: Transform) -> GridViewMut<'_, T>;
+    fn tilt<M, O>(&mut self, dir: &Direction, is_mobile: M, is_obstacle: O) -> usize
+    where
+        M: FnMut(&T) -> bool,
+        O: FnMut(&T) -> bool;
+    fn tilt_moves<M, O>(
+        &mut self,
+        dir: &Direction,
+        is_mobile: M,
+        is_obstacle: O,
+    ) -> Vec<(Coord, Coord)>
+    where
+        M: FnMut(&T) -> bool,
+        O: FnMut(&T) -> bool;
+    fn to_image<P>(&self, palette: P, scale: usize) -> Image
+    where
+        P: Fn(&T) -> Rgb;
+    fn to_svg<P>(&self, palette: P, scale: usize) -> Svg
+    where
+        P: Fn(&T) -> Rgb;
+    fn reflection_axes(&self, tolerance: usize) -> Vec<Reflection>
+    where
+        T: PartialEq;
+    fn rotational_symmetries(&self) -> Vec<Transform>
+    where
+        T: PartialEq;
+    fn tokens<V, P>(&self, lines: Lines, is_token: P) -> Vec<Token<V>>
+    where
+        T: fmt::Display,
+        V: FromStr,
+        P: FnMut(&T) -> bool;
+    fn find_pattern(&self, pattern: &Grid<T>, wildcard: Option<&T>) -> Vec<PatternMatch>
+    where
+        T: Clone + PartialEq;
+    fn find_pattern_transformed(
+        &self,
+        pattern: &Grid<T>,
+        wildcard: Option<&T>,
+        transforms: &[Transform],
+    ) -> Vec<PatternMatch>
+    where
+        T: Clone + PartialEq;
+    fn find_word(&self, word: &[T]) -> Vec<WordMatch>
+    where
+        T: PartialEq;
+    fn diff<'a>(&'a self, other: &'a Grid<T>) -> GridDiff<'a, T>
+    where
+        T: PartialEq;
+    fn to_graph<P>(&self, passable: P) -> GridGraph
+    where
+        P: FnMut(&T) -> bool;
+    fn to_graph_with<P>(&self, passable: P, points_of_interest: &[Coord]) -> GridGraph
+    where
+        P: FnMut(&T) -> bool;
+    fn padded(&self, rows: usize, cols: usize, fill: T) -> Grid<T>
+    where
+        T: Clone;
+    fn bordered(&self, width: usize, fill: T) -> Grid<T>
+    where
+        T: Clone;
 }
 
 impl<T> GridExt<T> for Grid<T> {
@@ -28,6 +146,16 @@ impl<T> GridExt<T> for Grid<T> {
         )
     }
 
+    // Every grid in the input, where grids are separated by blank lines. Handles CRLF and trailing newlines, and
+    // tells you which grid and line it choked on. Anything with `From<char>` works, since that gives `TryFrom`.
+    fn parse_blocks(input: &str) -> Result<Vec<Grid<T>>, ParseGridError>
+    where
+        Self: Sized,
+        T: TryFrom<char>,
+    {
+        blocks::parse_blocks(input)
+    }
+
     fn get_at_coord(&self, coord: &Coord) -> Option<&T> {
         self.get(coord.row, coord.col)
     }
@@ -53,6 +181,225 @@ impl<T> GridExt<T> for Grid<T> {
             })
         }
     }
+
+    // Every cell from `from` to `to`, including both ends. The two coordinates need to share a row, a column
+    // or a diagonal, otherwise there's no straight line between them and we return None.
+    fn line(&self, from: &Coord, to: &Coord) -> Option<Walk<'_, Grid<T>>> {
+        let d_row = to.row as isize - from.row as isize;
+        let d_col = to.col as isize - from.col as isize;
+        if d_row != 0 && d_col != 0 && d_row.abs() != d_col.abs() {
+            return None;
+        }
+        let len = d_row.unsigned_abs().max(d_col.unsigned_abs()) + 1;
+        Some(Walk::new(
+            self,
+            Some(*from),
+            (d_row.signum(), d_col.signum()),
+            Some(len),
+        ))
+    }
+
+    // Walks a whole row heading `dir`, so `Direction::West` starts at the last column and ends at the first
+    fn row_iter(&self, row: usize, dir: &Direction) -> Walk<'_, Grid<T>> {
+        let start = match dir {
+            Direction::East => Some(0),
+            Direction::West => self.cols().checked_sub(1),
+            _ => panic!("Rows can only be walked East or West, not {:?}", dir),
+        };
+        Walk::new(
+            self,
+            start.map(|col| Coord::new(row, col)),
+            dir.offset(),
+            None,
+        )
+    }
+
+    // Walks a whole column heading `dir`, so `Direction::North` starts at the bottom row and ends at the top
+    fn col_iter(&self, col: usize, dir: &Direction) -> Walk<'_, Grid<T>> {
+        let start = match dir {
+            Direction::South => Some(0),
+            Direction::North => self.rows().checked_sub(1),
+            _ => panic!("Columns can only be walked North or South, not {:?}", dir),
+        };
+        Walk::new(
+            self,
+            start.map(|row| Coord::new(row, col)),
+            dir.offset(),
+            None,
+        )
+    }
+
+    // Splits the grid up into orthogonally connected regions, where neighbours end up in the same region if
+    // `same_region` says so. Usually that's just `|a, b| a == b`.
+    fn regions<F>(&self, same_region: F) -> Regions
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        regions::regions(self, same_region)
+    }
+
+    // Look at the grid as if its edges were glued together. Nothing gets copied.
+    fn wrapping(&self) -> WrappingView<'_, T> {
+        WrappingView::new(self)
+    }
+
+    // Look at the grid as if it were repeated forever in every direction. Nothing gets copied.
+    fn tiled(&self) -> TiledView<'_, T> {
+        TiledView::new(self)
+    }
+
+    // Look at the grid rotated or flipped without cloning it. `view_mut` lets you write through the view too.
+    fn view(&self, transform: Transform) -> GridView<'_, T> {
+        GridView::new(self, transform)
+    }
+
+    fn view_mut(&mut self, transform: Transform) -> GridViewMut<'_, T> {
+        GridViewMut::new(self, transform)
+    }
+
+    // Tips the grid towards `dir` so every mobile cell slides until it hits an obstacle, the wall or another
+    // mobile cell. Returns how many cells moved.
+    fn tilt<M, O>(&mut self, dir: &Direction, is_mobile: M, is_obstacle: O) -> usize
+    where
+        M: FnMut(&T) -> bool,
+        O: FnMut(&T) -> bool,
+    {
+        tilt::tilt(self, dir, is_mobile, is_obstacle).len()
+    }
+
+    // Same as `tilt`, but tells you where each cell that moved started and where it ended up
+    fn tilt_moves<M, O>(
+        &mut self,
+        dir: &Direction,
+        is_mobile: M,
+        is_obstacle: O,
+    ) -> Vec<(Coord, Coord)>
+    where
+        M: FnMut(&T) -> bool,
+        O: FnMut(&T) -> bool,
+    {
+        tilt::tilt(self, dir, is_mobile, is_obstacle)
+    }
+
+    // Draws the grid as a picture, each cell a `scale` by `scale` square coloured by `palette`.
+    // Save it with `.save("out.png")` or `.save("out.ppm")`.
+    fn to_image<P>(&self, palette: P, scale: usize) -> Image
+    where
+        P: Fn(&T) -> Rgb,
+    {
+        Image::from_grid(self, palette, scale)
+    }
+
+    // Same idea as `to_image` but as an SVG, which can also have lines drawn over the top of it
+    fn to_svg<P>(&self, palette: P, scale: usize) -> Svg
+    where
+        P: Fn(&T) -> Rgb,
+    {
+        Svg::from_grid(self, palette, scale)
+    }
+
+    // Every mirror line, horizontal or vertical, where exactly `tolerance` pairs of cells fail to match.
+    // `reflection_axes(0)` finds perfect reflections, `reflection_axes(1)` finds the ones fixed by one smudge.
+    fn reflection_axes(&self, tolerance: usize) -> Vec<Reflection>
+    where
+        T: PartialEq,
+    {
+        reflect::reflection_axes(self, tolerance)
+    }
+
+    // The rotations that leave the grid looking exactly the same. Quarter turns only show up for square grids.
+    fn rotational_symmetries(&self) -> Vec<Transform>
+    where
+        T: PartialEq,
+    {
+        reflect::rotational_symmetries(self)
+    }
+
+    // Every run of cells matching `is_token` along `lines`, spelled out and parsed into a `V`. Part numbers in an
+    // engine schematic are `grid.tokens::<u32, _>(Lines::Rows, char::is_ascii_digit)`. Panics if a run doesn't
+    // parse, since that means `is_token` let through something it shouldn't have.
+    fn tokens<V, P>(&self, lines: Lines, is_token: P) -> Vec<Token<V>>
+    where
+        T: fmt::Display,
+        V: FromStr,
+        P: FnMut(&T) -> bool,
+    {
+        tokens::tokens(self, lines, is_token)
+    }
+
+    // Every place `pattern` fits in the grid as it is. Cells of the pattern equal to `wildcard` match anything,
+    // so a sea monster can be drawn with blanks around it.
+    fn find_pattern(&self, pattern: &Grid<T>, wildcard: Option<&T>) -> Vec<PatternMatch>
+    where
+        T: Clone + PartialEq,
+    {
+        pattern::find_pattern(self, pattern, wildcard, &[Transform::Identity])
+    }
+
+    // Same as `find_pattern`, but also tries the pattern under each of `transforms`. Pass `&Transform::ALL` to
+    // find it however it's been rotated or flipped.
+    fn find_pattern_transformed(
+        &self,
+        pattern: &Grid<T>,
+        wildcard: Option<&T>,
+        transforms: &[Transform],
+    ) -> Vec<PatternMatch>
+    where
+        T: Clone + PartialEq,
+    {
+        pattern::find_pattern(self, pattern, wildcard, transforms)
+    }
+
+    // Every place `word` reads in a straight line, forwards, backwards, up, down or diagonally
+    fn find_word(&self, word: &[T]) -> Vec<WordMatch>
+    where
+        T: PartialEq,
+    {
+        pattern::find_word(self, word)
+    }
+
+    // Every cell that's different in `other`, with what it was and what it is now. Both grids have to be the
+    // same size. `diff.print()` shows the two next to each other, which beats comparing printouts by eye.
+    fn diff<'a>(&'a self, other: &'a Grid<T>) -> GridDiff<'a, T>
+    where
+        T: PartialEq,
+    {
+        GridDiff::new(self, other)
+    }
+
+    // Squashes a maze down to its junctions and dead ends, with the corridors between them as weighted edges.
+    // Searching the graph is a lot quicker than searching every cell of a long winding corridor.
+    fn to_graph<P>(&self, passable: P) -> GridGraph
+    where
+        P: FnMut(&T) -> bool,
+    {
+        graph::to_graph(self, passable, &[])
+    }
+
+    // Same as `to_graph`, but every cell in `points_of_interest` becomes a node too, even halfway down a corridor.
+    // That's where the start and end usually go.
+    fn to_graph_with<P>(&self, passable: P, points_of_interest: &[Coord]) -> GridGraph
+    where
+        P: FnMut(&T) -> bool,
+    {
+        graph::to_graph(self, passable, points_of_interest)
+    }
+
+    // A copy grown on the right and bottom with `fill` until it's at least `rows` by `cols`
+    fn padded(&self, rows: usize, cols: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        blocks::padded(self, rows, cols, fill)
+    }
+
+    // A copy with `width` cells of `fill` all the way round. Good for not having to check the edges.
+    fn bordered(&self, width: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        blocks::bordered(self, width, fill)
+    }
 }
 
 // This is a supertrait. It's here so we can put all methods that require T to implement fmt::Debug in one place nice and clean
@@ -69,22 +416,12 @@ where
     T: fmt::Debug,
 {
     fn print(&self) {
-        for row in self.iter_rows() {
-            for item in row {
-                print!("{:?}", item)
-            }
-            println!()
-        }
+        Renderer::debug().print(self);
         println!()
     }
 
     fn print_with_spacing(&self, spacing: usize) {
-        for row in self.iter_rows() {
-            for item in row {
-                print!("{:width$?}", item, width = spacing)
-            }
-            println!()
-        }
+        Renderer::debug().spacing(spacing).print(self);
         println!()
     }
 }
@@ -99,6 +436,23 @@ impl Coord {
     pub fn new(row: usize, col: usize) -> Self {
         Coord { row, col }
     }
+
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    // Moves the coordinate by (rows, cols). This doesn't know anything about the grid, so the only thing that
+    // stops it is going below zero.
+    pub fn offset(&self, (d_row, d_col): (isize, isize)) -> Option<Coord> {
+        Some(Coord {
+            row: self.row.checked_add_signed(d_row)?,
+            col: self.col.checked_add_signed(d_col)?,
+        })
+    }
 }
 
 impl fmt::Debug for Coord {
@@ -114,3 +468,31 @@ pub enum Direction {
     South,
     West,
 }
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    // How far one step in this direction moves you as (rows, cols)
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Direction::North => (-1, 0),
+            Direction::East => (0, 1),
+            Direction::South => (1, 0),
+            Direction::West => (0, -1),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::East => Direction::West,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+        }
+    }
+}
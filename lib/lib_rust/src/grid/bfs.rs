@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+
+use super::{GridLike, Heading, PosMap, PosSet};
+
+// What comes out of `GridLike::bfs`. `dist` holds how many steps it took to reach each cell (missing if we never
+// got there) and `prev` holds the cell we came from, so we can walk a shortest path back to whichever source was
+// closest. On a dense `Grid` both of these are grids themselves, so `dist` is a `Grid<Option<u32>>`.
+pub struct DistanceMap<G: GridLike> {
+    pub dist: G::Map<u32>,
+    pub prev: G::Map<G::Pos>,
+}
+
+impl<G: GridLike> DistanceMap<G> {
+    pub fn distance(&self, pos: &G::Pos) -> Option<u32> {
+        self.dist.get(pos).copied()
+    }
+
+    // The shortest path from a source to `to`, including both ends
+    pub fn path_to(&self, to: &G::Pos) -> Option<Vec<G::Pos>> {
+        self.distance(to)?;
+        let mut path = vec![*to];
+        while let Some(prev) = self.prev.get(path.last().unwrap()) {
+            path.push(*prev);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    pub fn reachable(&self) -> impl Iterator<Item = G::Pos> + '_ {
+        self.dist.entries().map(|(pos, _)| pos)
+    }
+
+    // The cell furthest from every source, which is handy for "how far is the far side of the loop" questions
+    pub fn furthest(&self) -> Option<(G::Pos, u32)> {
+        self.dist
+            .entries()
+            .map(|(pos, dist)| (pos, *dist))
+            .max_by_key(|(_, dist)| *dist)
+    }
+}
+
+// Which cells count as touching. `Four` is just the cardinal directions, `Eight` adds the diagonals.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Connectivity {
+    Four,
+    Eight,
+}
+
+impl Connectivity {
+    pub fn offsets(&self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Four => &[(-1, 0), (0, 1), (1, 0), (0, -1)],
+            Connectivity::Eight => &[
+                (-1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+                (1, 0),
+                (1, -1),
+                (0, -1),
+                (-1, -1),
+            ],
+        }
+    }
+}
+
+pub(crate) fn bfs<G, P, S>(
+    grid: &G,
+    starts: &[G::Pos],
+    mut can_step: P,
+    visited: &mut S,
+) -> DistanceMap<G>
+where
+    G: GridLike,
+    P: FnMut(&G::Cell, &G::Cell, G::Direction) -> bool,
+    S: PosSet<G::Pos>,
+{
+    let mut dist = grid.new_map();
+    let mut prev = grid.new_map();
+    let mut queue = VecDeque::new();
+
+    for start in starts {
+        if grid.cell(start).is_some() && visited.insert(*start) {
+            dist.insert(*start, 0);
+            queue.push_back(*start);
+        }
+    }
+
+    while let Some(pos) = queue.pop_front() {
+        let pos_dist = *dist.get(&pos).unwrap();
+        let from = grid.cell(&pos).unwrap();
+        for &dir in G::Direction::ALL {
+            let Some(next) = grid.step(&pos, dir.offset()) else {
+                continue;
+            };
+            let Some(to) = grid.cell(&next) else {
+                continue;
+            };
+            if visited.contains(&next) || !can_step(from, to, dir) {
+                continue;
+            }
+            visited.insert(next);
+            dist.insert(next, pos_dist + 1);
+            prev.insert(next, pos);
+            queue.push_back(next);
+        }
+    }
+
+    DistanceMap { dist, prev }
+}
+
+pub(crate) fn flood_fill<G, P, S>(
+    grid: &G,
+    starts: &[G::Pos],
+    connectivity: G::Connectivity,
+    mut can_spread: P,
+    filled: &mut S,
+) where
+    G: GridLike,
+    P: FnMut(&G::Cell, &G::Cell) -> bool,
+    S: PosSet<G::Pos>,
+{
+    let mut stack = starts
+        .iter()
+        .filter(|start| grid.cell(start).is_some() && filled.insert(**start))
+        .copied()
+        .collect::<Vec<_>>();
+
+    while let Some(pos) = stack.pop() {
+        let from = grid.cell(&pos).unwrap();
+        for next in grid.neighbours(&pos, connectivity) {
+            if !filled.contains(&next) && can_spread(from, grid.cell(&next).unwrap()) {
+                filled.insert(next);
+                stack.push(next);
+            }
+        }
+    }
+}
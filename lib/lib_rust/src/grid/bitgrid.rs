@@ -0,0 +1,199 @@
+use std::ops::{BitAndAssign, BitOrAssign, SubAssign};
+
+use super::{Coord, Direction, PosSet};
+
+// A grid of flags packed into bits, `N` of them per cell. With the default of one bit it's just a yes/no per cell,
+// which is perfect as a visited set. With `BitGrid<4>` each cell can remember which directions something passed
+// through it in, e.g. `grid.set(&coord, dir as usize)`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct BitGrid<const N: usize = 1> {
+    rows: usize,
+    cols: usize,
+    words: Vec<u64>,
+}
+
+impl<const N: usize> BitGrid<N> {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        assert!(
+            (1..=64).contains(&N),
+            "A BitGrid needs between 1 and 64 bits per cell, not {}",
+            N
+        );
+        BitGrid {
+            rows,
+            cols,
+            words: vec![0; (rows * cols * N).div_ceil(64)],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    // Where a cell's `bit` lives in `words`, or None if the cell is off the grid
+    fn bit_index(&self, coord: &Coord, bit: usize) -> Option<usize> {
+        assert!(
+            bit < N,
+            "Bit {} is out of range, cells only have {}",
+            bit,
+            N
+        );
+        if coord.row >= self.rows || coord.col >= self.cols {
+            return None;
+        }
+        Some((coord.row * self.cols + coord.col) * N + bit)
+    }
+
+    pub fn get(&self, coord: &Coord, bit: usize) -> bool {
+        self.bit_index(coord, bit)
+            .is_some_and(|i| self.words[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    // Sets the bit and returns true if it wasn't already set. Panics if the cell is off the grid.
+    pub fn set(&mut self, coord: &Coord, bit: usize) -> bool {
+        let i = self
+            .bit_index(coord, bit)
+            .unwrap_or_else(|| panic!("{:?} is outside of the BitGrid", coord));
+        let was_set = self.words[i / 64] & (1 << (i % 64)) != 0;
+        self.words[i / 64] |= 1 << (i % 64);
+        !was_set
+    }
+
+    pub fn unset(&mut self, coord: &Coord, bit: usize) {
+        if let Some(i) = self.bit_index(coord, bit) {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    // Every bit of a cell packed together, bit 0 being the lowest
+    pub fn mask(&self, coord: &Coord) -> u64 {
+        (0..N)
+            .filter(|bit| self.get(coord, *bit))
+            .fold(0, |mask, bit| mask | 1 << bit)
+    }
+
+    pub fn set_mask(&mut self, coord: &Coord, mask: u64) {
+        for bit in 0..N {
+            if mask & (1 << bit) != 0 {
+                self.set(coord, bit);
+            } else {
+                self.unset(coord, bit);
+            }
+        }
+    }
+
+    // True if any of the cell's bits are set
+    pub fn any(&self, coord: &Coord) -> bool {
+        self.mask(coord) != 0
+    }
+
+    // How many bits are set across the whole grid
+    pub fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    // How many cells have at least one bit set, e.g. the number of energized tiles
+    pub fn count_cells(&self) -> usize {
+        if N == 1 {
+            self.count_ones()
+        } else {
+            self.iter().count()
+        }
+    }
+
+    // Every cell that has at least one bit set
+    pub fn iter(&self) -> impl Iterator<Item = Coord> + '_ {
+        (0..self.rows)
+            .flat_map(move |row| (0..self.cols).map(move |col| Coord::new(row, col)))
+            .filter(|coord| self.any(coord))
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn union(&self, other: &BitGrid<N>) -> BitGrid<N> {
+        let mut result = self.clone();
+        result |= other;
+        result
+    }
+
+    pub fn intersection(&self, other: &BitGrid<N>) -> BitGrid<N> {
+        let mut result = self.clone();
+        result &= other;
+        result
+    }
+
+    pub fn difference(&self, other: &BitGrid<N>) -> BitGrid<N> {
+        let mut result = self.clone();
+        result -= other;
+        result
+    }
+
+    // Moves every cell one step towards `dir`. Anything pushed off the edge is lost and the cells left behind
+    // on the opposite edge are empty.
+    pub fn shift(&self, dir: &Direction) -> BitGrid<N> {
+        let mut shifted = BitGrid::new(self.rows, self.cols);
+        for coord in self.iter() {
+            if let Some(next) = coord.offset(dir.offset()) {
+                if next.row < self.rows && next.col < self.cols {
+                    shifted.set_mask(&next, self.mask(&coord));
+                }
+            }
+        }
+        shifted
+    }
+
+    fn assert_same_size(&self, other: &BitGrid<N>) {
+        assert_eq!(
+            (self.rows, self.cols),
+            (other.rows, other.cols),
+            "BitGrids need to be the same size to be combined"
+        );
+    }
+}
+
+impl<const N: usize> BitOrAssign<&BitGrid<N>> for BitGrid<N> {
+    fn bitor_assign(&mut self, other: &BitGrid<N>) {
+        self.assert_same_size(other);
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+}
+
+impl<const N: usize> BitAndAssign<&BitGrid<N>> for BitGrid<N> {
+    fn bitand_assign(&mut self, other: &BitGrid<N>) {
+        self.assert_same_size(other);
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= other;
+        }
+    }
+}
+
+impl<const N: usize> SubAssign<&BitGrid<N>> for BitGrid<N> {
+    fn sub_assign(&mut self, other: &BitGrid<N>) {
+        self.assert_same_size(other);
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other;
+        }
+    }
+}
+
+// Lets a one bit grid be the visited set for `bfs_with` and `flood_fill_with`
+impl PosSet<Coord> for BitGrid {
+    fn contains(&self, pos: &Coord) -> bool {
+        self.get(pos, 0)
+    }
+
+    fn insert(&mut self, pos: Coord) -> bool {
+        self.set(&pos, 0)
+    }
+}
@@ -0,0 +1,187 @@
+use std::error::Error;
+use std::fmt;
+
+use grid::Grid;
+
+// Helpers for puzzles with more than one grid in them: parsing a blank-line separated list of grids and putting
+// several grids together into one.
+
+// What went wrong in `GridExt::parse_blocks`. `block` counts from zero, `line` is the line number in the whole
+// input counting from one, like an editor would show it.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum ParseGridError {
+    Ragged {
+        block: usize,
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    BadChar {
+        block: usize,
+        line: usize,
+        col: usize,
+        found: char,
+    },
+}
+
+impl fmt::Display for ParseGridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseGridError::Ragged {
+                block,
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Grid {} line {} is {} long, expected {}",
+                block, line, found, expected
+            ),
+            ParseGridError::BadChar {
+                block,
+                line,
+                col,
+                found,
+            } => write!(
+                f,
+                "Grid {} line {} column {} has {:?}, which isn't a valid cell",
+                block, line, col, found
+            ),
+        }
+    }
+}
+
+impl Error for ParseGridError {}
+
+// Blocks are separated by blank lines. `lines` already strips the \r off CRLF line endings, and lines of nothing
+// but whitespace count as blank, so trailing newlines and stray spaces don't make empty grids.
+pub(crate) fn parse_blocks<T>(input: &str) -> Result<Vec<Grid<T>>, ParseGridError>
+where
+    T: TryFrom<char>,
+{
+    let mut blocks: Vec<Vec<(usize, &str)>> = Vec::new();
+    let mut current = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                blocks.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push((i + 1, line));
+        }
+    }
+    if !current.is_empty() {
+        blocks.push(current);
+    }
+
+    blocks
+        .iter()
+        .enumerate()
+        .map(|(block, lines)| parse_block(block, lines))
+        .collect()
+}
+
+fn parse_block<T>(block: usize, lines: &[(usize, &str)]) -> Result<Grid<T>, ParseGridError>
+where
+    T: TryFrom<char>,
+{
+    let cols = lines[0].1.chars().count();
+    let mut cells = Vec::with_capacity(cols * lines.len());
+    for (line, text) in lines {
+        let found = text.chars().count();
+        if found != cols {
+            return Err(ParseGridError::Ragged {
+                block,
+                line: *line,
+                expected: cols,
+                found,
+            });
+        }
+        for (col, c) in text.chars().enumerate() {
+            let item = T::try_from(c).map_err(|_| ParseGridError::BadChar {
+                block,
+                line: *line,
+                col: col + 1,
+                found: c,
+            })?;
+            cells.push(item);
+        }
+    }
+    Ok(Grid::from_vec(cells, cols))
+}
+
+// Puts the grids next to each other left to right with `gap` columns of `fill` between them. Shorter grids get
+// `fill` underneath to make them all as tall as the tallest.
+pub fn hstack<T>(grids: &[Grid<T>], gap: usize, fill: T) -> Grid<T>
+where
+    T: Clone,
+{
+    let rows = grids.iter().map(|grid| grid.rows()).max().unwrap_or(0);
+    let cols =
+        grids.iter().map(|grid| grid.cols()).sum::<usize>() + gap * grids.len().saturating_sub(1);
+    let mut stacked = Grid::init(rows, cols, fill);
+    let mut left = 0;
+    for grid in grids {
+        paste(&mut stacked, grid, 0, left);
+        left += grid.cols() + gap;
+    }
+    stacked
+}
+
+// Same as `hstack` but top to bottom, padding narrower grids on the right
+pub fn vstack<T>(grids: &[Grid<T>], gap: usize, fill: T) -> Grid<T>
+where
+    T: Clone,
+{
+    let rows =
+        grids.iter().map(|grid| grid.rows()).sum::<usize>() + gap * grids.len().saturating_sub(1);
+    let cols = grids.iter().map(|grid| grid.cols()).max().unwrap_or(0);
+    let mut stacked = Grid::init(rows, cols, fill);
+    let mut top = 0;
+    for grid in grids {
+        paste(&mut stacked, grid, top, 0);
+        top += grid.rows() + gap;
+    }
+    stacked
+}
+
+// Pads every grid on the right and bottom with `fill` until they're all the size of the biggest one
+pub fn pad_to_common<T>(grids: &[Grid<T>], fill: T) -> Vec<Grid<T>>
+where
+    T: Clone,
+{
+    let rows = grids.iter().map(|grid| grid.rows()).max().unwrap_or(0);
+    let cols = grids.iter().map(|grid| grid.cols()).max().unwrap_or(0);
+    grids
+        .iter()
+        .map(|grid| padded(grid, rows, cols, fill.clone()))
+        .collect()
+}
+
+pub(crate) fn padded<T>(grid: &Grid<T>, rows: usize, cols: usize, fill: T) -> Grid<T>
+where
+    T: Clone,
+{
+    let mut padded = Grid::init(rows.max(grid.rows()), cols.max(grid.cols()), fill);
+    paste(&mut padded, grid, 0, 0);
+    padded
+}
+
+pub(crate) fn bordered<T>(grid: &Grid<T>, width: usize, fill: T) -> Grid<T>
+where
+    T: Clone,
+{
+    let mut bordered = Grid::init(grid.rows() + 2 * width, grid.cols() + 2 * width, fill);
+    paste(&mut bordered, grid, width, width);
+    bordered
+}
+
+// Copies `grid` into `into` with its top left corner at (top, left)
+fn paste<T>(into: &mut Grid<T>, grid: &Grid<T>, top: usize, left: usize)
+where
+    T: Clone,
+{
+    for ((row, col), item) in grid.indexed_iter() {
+        into[(top + row, left + col)] = item.clone();
+    }
+}
@@ -0,0 +1,104 @@
+use grid::Grid;
+
+use super::Coord;
+
+// Gives every row and column of a grid a weight, i.e. how many rows or columns it really stands for. Think of an
+// empty row in the galaxy map that's actually a million rows tall.
+// We keep running totals of the weights, so the real distance between any two cells is just two subtractions
+// instead of a walk over every row and column in between.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compression {
+    // `row_starts[i]` is the real row that compressed row `i` starts at, which is the sum of the weights before it.
+    // There's one more entry than there are rows, holding the total.
+    row_starts: Vec<u64>,
+    col_starts: Vec<u64>,
+}
+
+impl Compression {
+    pub fn new(row_weights: &[u64], col_weights: &[u64]) -> Self {
+        Compression {
+            row_starts: running_totals(row_weights),
+            col_starts: running_totals(col_weights),
+        }
+    }
+
+    // Every row and column that's entirely `is_empty` weighs `empty_weight`, everything else weighs 1
+    pub fn expand_empty<T, F>(grid: &Grid<T>, mut is_empty: F, empty_weight: u64) -> Self
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let row_weights = (0..grid.rows())
+            .map(|row| {
+                if grid.iter_row(row).all(&mut is_empty) {
+                    empty_weight
+                } else {
+                    1
+                }
+            })
+            .collect::<Vec<_>>();
+        let col_weights = (0..grid.cols())
+            .map(|col| {
+                if grid.iter_col(col).all(&mut is_empty) {
+                    empty_weight
+                } else {
+                    1
+                }
+            })
+            .collect::<Vec<_>>();
+        Compression::new(&row_weights, &col_weights)
+    }
+
+    // The real size of the grid once every row and column is expanded
+    pub fn size(&self) -> (u64, u64) {
+        (
+            *self.row_starts.last().unwrap(),
+            *self.col_starts.last().unwrap(),
+        )
+    }
+
+    pub fn row_distance(&self, a: usize, b: usize) -> u64 {
+        self.row_starts[a].abs_diff(self.row_starts[b])
+    }
+
+    pub fn col_distance(&self, a: usize, b: usize) -> u64 {
+        self.col_starts[a].abs_diff(self.col_starts[b])
+    }
+
+    // The real Manhattan distance between two compressed cells
+    pub fn distance(&self, a: &Coord, b: &Coord) -> u64 {
+        self.row_distance(a.row, b.row) + self.col_distance(a.col, b.col)
+    }
+
+    // Where a compressed cell really starts, as (row, col)
+    pub fn expand(&self, coord: &Coord) -> Option<(u64, u64)> {
+        if coord.row >= self.row_starts.len() - 1 || coord.col >= self.col_starts.len() - 1 {
+            return None;
+        }
+        Some((self.row_starts[coord.row], self.col_starts[coord.col]))
+    }
+
+    // Which compressed cell a real (row, col) lands in
+    pub fn compress(&self, (row, col): (u64, u64)) -> Option<Coord> {
+        Some(Coord::new(
+            find_start(&self.row_starts, row)?,
+            find_start(&self.col_starts, col)?,
+        ))
+    }
+}
+
+fn running_totals(weights: &[u64]) -> Vec<u64> {
+    let mut totals = Vec::with_capacity(weights.len() + 1);
+    totals.push(0);
+    for weight in weights {
+        totals.push(totals.last().unwrap() + weight);
+    }
+    totals
+}
+
+// The index of the last start that's <= `real`, as long as `real` comes before the total
+fn find_start(starts: &[u64], real: u64) -> Option<usize> {
+    if real >= *starts.last().unwrap() {
+        return None;
+    }
+    Some(starts.partition_point(|start| *start <= real) - 1)
+}
@@ -0,0 +1,63 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+// For simulations that eventually loop, like spinning the platform a billion times. Rather than run every step we
+// remember every state we've seen, and as soon as one comes round again we know the rest is just that loop
+// repeating, so we can jump straight to the answer.
+
+// `prefix` is how many steps it takes before the loop starts and `len` is how many steps the loop takes.
+// So the state after `prefix` steps is the same as the state after `prefix + len` steps.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct CycleInfo {
+    pub prefix: usize,
+    pub len: usize,
+}
+
+pub struct Simulated<S> {
+    pub state: S,
+    // None if we got to the end before anything repeated
+    pub cycle: Option<CycleInfo>,
+}
+
+// Runs `step` on `initial` `steps` times and hands back the state at the end. `steps` can be something silly like
+// 10^18 as long as the states start repeating at some point.
+pub fn simulate<S, F>(initial: S, steps: u64, mut step: F) -> Simulated<S>
+where
+    S: Hash + Eq + Clone,
+    F: FnMut(&mut S),
+{
+    // States are looked up by their hash first, then compared properly in case two different states share a hash
+    let mut history: Vec<S> = Vec::new();
+    let mut seen: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut state = initial;
+
+    for i_step in 0..steps {
+        let fingerprint = fingerprint(&state);
+        let earlier = seen
+            .get(&fingerprint)
+            .and_then(|indexes| indexes.iter().find(|&&i| history[i] == state));
+        if let Some(&prefix) = earlier {
+            let cycle = CycleInfo {
+                prefix,
+                len: i_step as usize - prefix,
+            };
+            let offset = ((steps - prefix as u64) % cycle.len as u64) as usize;
+            return Simulated {
+                state: history.swap_remove(prefix + offset),
+                cycle: Some(cycle),
+            };
+        }
+        seen.entry(fingerprint).or_default().push(history.len());
+        history.push(state.clone());
+        step(&mut state);
+    }
+
+    Simulated { state, cycle: None }
+}
+
+pub fn fingerprint<S: Hash>(state: &S) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    state.hash(&mut hasher);
+    hasher.finish()
+}
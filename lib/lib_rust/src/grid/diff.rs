@@ -0,0 +1,194 @@
+use std::fmt;
+
+use colored::Color;
+use grid::Grid;
+
+use super::{BitGrid, Coord, Renderer};
+
+// One cell that's different between two grids
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Change<'a, T> {
+    pub coord: Coord,
+    pub old: &'a T,
+    pub new: &'a T,
+}
+
+// Which cells changed, plus which rows and columns they're in. A simulation that only cares about what moved
+// last step can loop over `rows()` or `cols()` instead of the whole grid.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct ChangedCells {
+    cells: BitGrid,
+    rows: Vec<bool>,
+    cols: Vec<bool>,
+    len: usize,
+}
+
+impl ChangedCells {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        ChangedCells {
+            cells: BitGrid::new(rows, cols),
+            rows: vec![false; rows],
+            cols: vec![false; cols],
+            len: 0,
+        }
+    }
+
+    // Returns true if the cell wasn't already marked. Panics if it's off the grid.
+    pub fn mark(&mut self, coord: &Coord) -> bool {
+        if !self.cells.set(coord, 0) {
+            return false;
+        }
+        self.rows[coord.row] = true;
+        self.cols[coord.col] = true;
+        self.len += 1;
+        true
+    }
+
+    pub fn contains(&self, coord: &Coord) -> bool {
+        self.cells.get(coord, 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Coord> + '_ {
+        self.cells.iter()
+    }
+
+    // Every row with at least one changed cell, in order
+    pub fn rows(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.rows.len()).filter(|row| self.rows[*row])
+    }
+
+    pub fn cols(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.cols.len()).filter(|col| self.cols[*col])
+    }
+
+    pub fn row_changed(&self, row: usize) -> bool {
+        self.rows.get(row).copied().unwrap_or(false)
+    }
+
+    pub fn col_changed(&self, col: usize) -> bool {
+        self.cols.get(col).copied().unwrap_or(false)
+    }
+
+    // Starts over without giving back the memory, for reusing the same set every step
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.rows.fill(false);
+        self.cols.fill(false);
+        self.len = 0;
+    }
+}
+
+impl Extend<Coord> for ChangedCells {
+    fn extend<I: IntoIterator<Item = Coord>>(&mut self, coords: I) {
+        for coord in coords {
+            self.mark(&coord);
+        }
+    }
+}
+
+// What comes back from `GridExt::diff`. It keeps hold of both grids so it can draw them next to each other.
+pub struct GridDiff<'a, T> {
+    before: &'a Grid<T>,
+    after: &'a Grid<T>,
+    changes: Vec<Change<'a, T>>,
+}
+
+impl<'a, T> GridDiff<'a, T> {
+    pub(crate) fn new(before: &'a Grid<T>, after: &'a Grid<T>) -> Self
+    where
+        T: PartialEq,
+    {
+        assert_eq!(
+            before.size(),
+            after.size(),
+            "Can only diff grids that are the same size"
+        );
+        let changes = before
+            .indexed_iter()
+            .zip(after.iter())
+            .filter(|((_, old), new)| old != new)
+            .map(|(((row, col), old), new)| Change {
+                coord: Coord::new(row, col),
+                old,
+                new,
+            })
+            .collect();
+        GridDiff {
+            before,
+            after,
+            changes,
+        }
+    }
+
+    // In reading order
+    pub fn changes(&self) -> &[Change<'a, T>] {
+        &self.changes
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn changed_cells(&self) -> ChangedCells {
+        let mut changed = ChangedCells::new(self.before.rows(), self.before.cols());
+        changed.extend(self.changes.iter().map(|change| change.coord));
+        changed
+    }
+
+    // The old grid on the left and the new one on the right, with changed cells picked out in red on the old side
+    // and green on the new side
+    pub fn render_with<F>(&self, format_cell: F) -> String
+    where
+        F: Fn(&T) -> String + Clone,
+    {
+        let coords = self.changes.iter().map(|change| change.coord);
+        let before = Renderer::new(format_cell.clone())
+            .highlight(coords.clone(), Color::Red)
+            .render(self.before);
+        let after = Renderer::new(format_cell.clone())
+            .highlight(coords, Color::Green)
+            .render(self.after);
+        // Colour codes throw off the width of each line, so pad using the uncoloured version
+        let plain = Renderer::new(format_cell).color(false).render(self.before);
+        let width = plain.lines().map(|line| line.chars().count()).max();
+
+        let mut out = String::new();
+        for ((left, plain_left), right) in before.lines().zip(plain.lines()).zip(after.lines()) {
+            let padding = width.unwrap_or(0) - plain_left.chars().count();
+            out.push_str(&format!("{}{:padding$} | {}\n", left, "", right));
+        }
+        out
+    }
+
+    pub fn print_with<F>(&self, format_cell: F)
+    where
+        F: Fn(&T) -> String + Clone,
+    {
+        print!("{}", self.render_with(format_cell))
+    }
+}
+
+impl<T> GridDiff<'_, T>
+where
+    T: fmt::Debug,
+{
+    pub fn render(&self) -> String {
+        self.render_with(|item: &T| format!("{:?}", item))
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.render())
+    }
+}
@@ -0,0 +1,406 @@
+use std::collections::HashSet;
+
+use grid::Grid;
+
+use super::{Coord, Direction, GridExt, SignedCoord, SparseGrid};
+
+// Maths for closed loops on a grid. Every vertex sits on a lattice point, so the shoelace formula gives us the
+// exact area (well, twice the area, which is always a whole number) and Pick's theorem turns that into the number
+// of lattice points inside the loop. Vertices are `SignedCoord`s, since a dig plan can wander off above or to the
+// left of where it started. Areas are done in i128 so huge loops don't overflow.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polygon {
+    pub vertices: Vec<SignedCoord>,
+}
+
+impl Polygon {
+    // The loop is closed for us, so don't repeat the first coordinate at the end. Consecutive coordinates can be
+    // neighbours (like the segments of a pipe) or corners with a straight line between them. Takes either plain
+    // `Coord`s or `SignedCoord`s.
+    pub fn from_coords<C>(coords: &[C]) -> Self
+    where
+        C: Into<SignedCoord> + Copy,
+    {
+        Polygon {
+            vertices: coords.iter().map(|coord| (*coord).into()).collect(),
+        }
+    }
+
+    // Follows a list of moves starting from (0, 0), e.g. the lines of a dig plan
+    pub fn from_moves(moves: &[(Direction, i64)]) -> Self {
+        let mut vertices = Vec::with_capacity(moves.len());
+        let mut current = SignedCoord::new(0, 0);
+        for (dir, len) in moves {
+            let (d_row, d_col) = dir.offset();
+            current = SignedCoord::new(
+                current.row() + d_row as i64 * len,
+                current.col() + d_col as i64 * len,
+            );
+            vertices.push(current);
+        }
+        Polygon { vertices }
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (SignedCoord, SignedCoord)> + '_ {
+        self.vertices
+            .iter()
+            .copied()
+            .zip(self.vertices.iter().copied().cycle().skip(1))
+    }
+
+    // The shoelace formula. We hand back twice the area so it stays exact, since a loop with diagonal edges can
+    // enclose half a cell.
+    pub fn doubled_area(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| a.col() as i128 * b.row() as i128 - b.col() as i128 * a.row() as i128)
+            .sum::<i128>()
+            .abs()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.doubled_area() as f64 / 2.0
+    }
+
+    // How many lattice points sit on the loop itself. When every edge is horizontal or vertical this is the
+    // length of the boundary.
+    pub fn boundary_points(&self) -> i128 {
+        self.edges()
+            .map(|(a, b)| {
+                gcd(
+                    (b.row() - a.row()).unsigned_abs(),
+                    (b.col() - a.col()).unsigned_abs(),
+                ) as i128
+            })
+            .sum()
+    }
+
+    // Pick's theorem says A = I + B/2 - 1, so I = (2A - B + 2) / 2. For a pipe loop this is the number of tiles
+    // enclosed by the loop.
+    pub fn interior_points(&self) -> i128 {
+        (self.doubled_area() - self.boundary_points() + 2) / 2
+    }
+
+    // Every cell on or inside the loop, which is what you want when the loop is the outline of a hole being dug
+    pub fn covered_points(&self) -> i128 {
+        self.interior_points() + self.boundary_points()
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// Every lattice point from `from` to `to`, both ends included, using Bresenham's algorithm. Horizontal, vertical
+// and 45° lines come out exactly, anything else gets the usual staircase.
+pub fn bresenham(from: SignedCoord, to: SignedCoord) -> Bresenham {
+    let (d_row, d_col) = (to.row() - from.row(), to.col() - from.col());
+    Bresenham {
+        current: from,
+        end: to,
+        d_row: -d_row.abs(),
+        d_col: d_col.abs(),
+        step: (d_row.signum(), d_col.signum()),
+        err: d_col.abs() - d_row.abs(),
+        done: false,
+    }
+}
+
+pub struct Bresenham {
+    current: SignedCoord,
+    end: SignedCoord,
+    d_row: i64,
+    d_col: i64,
+    step: (i64, i64),
+    err: i64,
+    done: bool,
+}
+
+impl Iterator for Bresenham {
+    type Item = SignedCoord;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let point = self.current;
+        if point == self.end {
+            self.done = true;
+            return Some(point);
+        }
+        let (mut row, mut col) = (point.row(), point.col());
+        let doubled = 2 * self.err;
+        if doubled >= self.d_row {
+            self.err += self.d_row;
+            col += self.step.1;
+        }
+        if doubled <= self.d_col {
+            self.err += self.d_col;
+            row += self.step.0;
+        }
+        self.current = SignedCoord::new(row, col);
+        Some(point)
+    }
+}
+
+// A line between two lattice points, ends included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Segment {
+    pub from: SignedCoord,
+    pub to: SignedCoord,
+}
+
+impl Segment {
+    pub fn new(from: SignedCoord, to: SignedCoord) -> Self {
+        Segment { from, to }
+    }
+
+    pub fn from_coords(from: &Coord, to: &Coord) -> Self {
+        Segment::new((*from).into(), (*to).into())
+    }
+
+    pub fn is_axis_aligned(&self) -> bool {
+        self.from.row() == self.to.row() || self.from.col() == self.to.col()
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        let (d_row, d_col) = self.delta();
+        d_row.abs() == d_col.abs()
+    }
+
+    // Horizontal, vertical or 45°, which are the lines that go through a lattice point at every step
+    pub fn is_straight(&self) -> bool {
+        self.is_axis_aligned() || self.is_diagonal()
+    }
+
+    pub fn points(&self) -> Bresenham {
+        bresenham(self.from, self.to)
+    }
+
+    pub fn bounding_box(&self) -> Rect {
+        Rect::from_corners(self.from, self.to)
+    }
+
+    fn delta(&self) -> (i64, i64) {
+        (
+            self.to.row() - self.from.row(),
+            self.to.col() - self.from.col(),
+        )
+    }
+
+    // One step along a straight segment and how many steps it takes to get from one end to the other
+    fn step(&self) -> ((i64, i64), i64) {
+        let (d_row, d_col) = self.delta();
+        (
+            (d_row.signum(), d_col.signum()),
+            d_row.abs().max(d_col.abs()),
+        )
+    }
+
+    // `t` steps along from `from`
+    fn at(&self, t: i64) -> SignedCoord {
+        let ((s_row, s_col), _) = self.step();
+        SignedCoord::new(self.from.row() + s_row * t, self.from.col() + s_col * t)
+    }
+
+    pub fn contains(&self, point: &SignedCoord) -> bool {
+        if !self.is_straight() {
+            return self.points().any(|p| p == *point);
+        }
+        let (_, len) = self.step();
+        let (d_row, d_col) = (point.row() - self.from.row(), point.col() - self.from.col());
+        // The point has to be a whole number of steps along the line, and no more steps than the line is long
+        let t = d_row.abs().max(d_col.abs());
+        t <= len && self.at(t) == *point
+    }
+
+    // Every lattice point the two segments share. For straight segments this is worked out directly, so it's
+    // fine on huge coordinates, otherwise we compare the rasterised points. Two diagonals that cross between
+    // lattice points don't share any.
+    pub fn intersection(&self, other: &Segment) -> Vec<SignedCoord> {
+        if !self.is_straight() || !other.is_straight() {
+            let theirs = other.points().collect::<HashSet<_>>();
+            return self.points().filter(|p| theirs.contains(p)).collect();
+        }
+        let ((s_row, s_col), len) = self.step();
+        let ((o_row, o_col), other_len) = other.step();
+        let (b_row, b_col) = (
+            other.from.row() - self.from.row(),
+            other.from.col() - self.from.col(),
+        );
+        let cross = s_row * o_col - s_col * o_row;
+
+        if len == 0 || other_len == 0 || cross != 0 {
+            // Point-like or crossing segments meet in at most one point, a + t * s = b + u * o. A single point has
+            // no direction, so check it directly against the other segment.
+            let candidate = if len == 0 {
+                Some(self.from)
+            } else if other_len == 0 {
+                Some(other.from)
+            } else {
+                let t_scaled = b_row * o_col - b_col * o_row;
+                (t_scaled % cross == 0).then(|| self.at(t_scaled / cross))
+            };
+            return candidate
+                .filter(|p| self.contains(p) && other.contains(p))
+                .into_iter()
+                .collect();
+        }
+
+        // Parallel. They only share points if they're on the same line, in which case it's wherever the ranges
+        // of steps along our line overlap.
+        if b_row * s_col - b_col * s_row != 0 {
+            return Vec::new();
+        }
+        let steps_to = |p: SignedCoord| {
+            let (d_row, d_col) = (p.row() - self.from.row(), p.col() - self.from.col());
+            (d_row * s_row + d_col * s_col) / (s_row * s_row + s_col * s_col)
+        };
+        let (a, b) = (steps_to(other.from), steps_to(other.to));
+        (a.min(b).max(0)..=a.max(b).min(len))
+            .map(|t| self.at(t))
+            .collect()
+    }
+
+    pub fn intersects(&self, other: &Segment) -> bool {
+        !self.intersection(other).is_empty()
+    }
+}
+
+// An axis-aligned rectangle of lattice points, both corners included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub min: SignedCoord,
+    pub max: SignedCoord,
+}
+
+impl Rect {
+    // Any two opposite corners, in any order
+    pub fn from_corners(a: SignedCoord, b: SignedCoord) -> Self {
+        Rect {
+            min: SignedCoord::new(a.row().min(b.row()), a.col().min(b.col())),
+            max: SignedCoord::new(a.row().max(b.row()), a.col().max(b.col())),
+        }
+    }
+
+    pub fn rows(&self) -> u64 {
+        (self.max.row() - self.min.row()) as u64 + 1
+    }
+
+    pub fn cols(&self) -> u64 {
+        (self.max.col() - self.min.col()) as u64 + 1
+    }
+
+    // The number of lattice points inside, edges included
+    pub fn area(&self) -> u64 {
+        self.rows() * self.cols()
+    }
+
+    pub fn contains(&self, point: &SignedCoord) -> bool {
+        (self.min.row()..=self.max.row()).contains(&point.row())
+            && (self.min.col()..=self.max.col()).contains(&point.col())
+    }
+
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let min = SignedCoord::new(
+            self.min.row().max(other.min.row()),
+            self.min.col().max(other.min.col()),
+        );
+        let max = SignedCoord::new(
+            self.max.row().min(other.max.row()),
+            self.max.col().min(other.max.col()),
+        );
+        (min.row() <= max.row() && min.col() <= max.col()).then_some(Rect { min, max })
+    }
+
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    // Whether any lattice point of the segment lands in the rectangle. Straight segments are clipped against
+    // each side in turn, since every step moves at most one along each axis.
+    pub fn overlaps_segment(&self, segment: &Segment) -> bool {
+        if !segment.is_straight() {
+            return segment.points().any(|p| self.contains(&p));
+        }
+        let ((s_row, s_col), len) = segment.step();
+        let (mut lo, mut hi) = (0, len);
+        for (start, step, min, max) in [
+            (segment.from.row(), s_row, self.min.row(), self.max.row()),
+            (segment.from.col(), s_col, self.min.col(), self.max.col()),
+        ] {
+            match step {
+                0 if start < min || start > max => return false,
+                0 => (),
+                1 => {
+                    lo = lo.max(min - start);
+                    hi = hi.min(max - start);
+                }
+                _ => {
+                    lo = lo.max(start - max);
+                    hi = hi.min(start - min);
+                }
+            }
+        }
+        lo <= hi
+    }
+}
+
+// Every lattice point along the polyline, following it from the first vertex to the last. Where one segment
+// joins the next the corner only shows up once.
+pub fn polyline_points(vertices: &[SignedCoord]) -> Vec<SignedCoord> {
+    let mut points = vertices.first().copied().into_iter().collect::<Vec<_>>();
+    for pair in vertices.windows(2) {
+        points.extend(bresenham(pair[0], pair[1]).skip(1));
+    }
+    points
+}
+
+// Sets every cell along the polyline to `value`, like the rock paths in a falling sand puzzle. Anything that
+// falls off the grid is skipped. Returns how many different cells were set, so a corner the path comes back
+// through only counts once.
+pub fn draw_polyline<T>(grid: &mut Grid<T>, vertices: &[Coord], value: T) -> usize
+where
+    T: Clone,
+{
+    let vertices = vertices
+        .iter()
+        .map(|coord| SignedCoord::from(*coord))
+        .collect::<Vec<_>>();
+    let mut drawn = 0;
+    for point in polyline_points(&vertices)
+        .into_iter()
+        .collect::<HashSet<_>>()
+    {
+        let (Ok(row), Ok(col)) = (usize::try_from(point.row()), usize::try_from(point.col()))
+        else {
+            continue;
+        };
+        if let Some(cell) = grid.get_at_coord_mut(&Coord::new(row, col)) {
+            *cell = value.clone();
+            drawn += 1;
+        }
+    }
+    drawn
+}
+
+// Same as `draw_polyline` but the grid grows to fit, so nothing gets skipped
+pub fn draw_polyline_sparse<T>(
+    grid: &mut SparseGrid<T>,
+    vertices: &[SignedCoord],
+    value: T,
+) -> usize
+where
+    T: Clone,
+{
+    let points = polyline_points(vertices)
+        .into_iter()
+        .collect::<HashSet<_>>();
+    for point in &points {
+        grid.insert(*point, value.clone());
+    }
+    points.len()
+}
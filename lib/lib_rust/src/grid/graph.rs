@@ -0,0 +1,192 @@
+use std::collections::{HashMap, HashSet};
+
+use grid::Grid;
+
+use super::search::{self, SearchPath};
+use super::{Connectivity, Coord, GridLike};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+// A corridor from one node to another. `cells` are the cells in between, in the order you'd walk them, so `len`
+// is always one more than the number of cells.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Edge {
+    pub to: NodeId,
+    pub len: u64,
+    pub cells: Vec<Coord>,
+}
+
+// A maze squashed down to the cells that matter. Every junction, dead end and point of interest becomes a node,
+// and the corridors between them become edges. Each corridor shows up once from either end, so `edges` of both
+// of its nodes have it.
+// A loop of corridor with no node anywhere on it can't be reached from a node, so it gets left out.
+#[derive(Clone, Debug)]
+pub struct GridGraph {
+    nodes: Vec<Coord>,
+    index: HashMap<Coord, NodeId>,
+    edges: Vec<Vec<Edge>>,
+}
+
+impl GridGraph {
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, Coord)> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .map(|(i, coord)| (NodeId(i), *coord))
+    }
+
+    pub fn coord(&self, node: NodeId) -> Coord {
+        self.nodes[node.0]
+    }
+
+    pub fn node_at(&self, coord: &Coord) -> Option<NodeId> {
+        self.index.get(coord).copied()
+    }
+
+    pub fn edges(&self, node: NodeId) -> &[Edge] {
+        &self.edges[node.0]
+    }
+
+    // In the shape `search::dijkstra` and friends want, e.g.
+    // `dijkstra(&[start], |node| graph.successors(*node), |node| *node == goal)`
+    pub fn successors(&self, node: NodeId) -> impl Iterator<Item = (NodeId, u64)> + '_ {
+        self.edges[node.0].iter().map(|edge| (edge.to, edge.len))
+    }
+
+    pub fn shortest_path(&self, from: NodeId, to: NodeId) -> Option<SearchPath<NodeId>> {
+        search::dijkstra(
+            &[from],
+            |node| self.successors(*node).collect::<Vec<_>>(),
+            |node| *node == to,
+        )
+    }
+
+    // The longest route from `from` to `to` that never visits a node twice. This is NP-hard in general, so it's a
+    // plain depth first search over every route. That's fine once the corridors have been squashed out, since
+    // puzzle mazes tend to end up with a few dozen nodes at most.
+    pub fn longest_path(&self, from: NodeId, to: NodeId) -> Option<u64> {
+        let mut visited = vec![false; self.nodes.len()];
+        visited[from.0] = true;
+        self.longest_from(from, to, &mut visited)
+    }
+
+    fn longest_from(&self, node: NodeId, to: NodeId, visited: &mut [bool]) -> Option<u64> {
+        if node == to {
+            return Some(0);
+        }
+        let mut longest = None;
+        for edge in &self.edges[node.0] {
+            if visited[edge.to.0] {
+                continue;
+            }
+            visited[edge.to.0] = true;
+            if let Some(rest) = self.longest_from(edge.to, to, visited) {
+                longest = longest.max(Some(edge.len + rest));
+            }
+            visited[edge.to.0] = false;
+        }
+        longest
+    }
+
+    // Turns a route through the graph back into every cell along it, both ends included. Where two nodes are
+    // joined by more than one corridor the shortest one is used.
+    pub fn expand(&self, path: &[NodeId]) -> Vec<Coord> {
+        let mut cells = path
+            .first()
+            .map(|node| self.coord(*node))
+            .into_iter()
+            .collect::<Vec<_>>();
+        for pair in path.windows(2) {
+            let edge = self.edges[pair[0].0]
+                .iter()
+                .filter(|edge| edge.to == pair[1])
+                .min_by_key(|edge| edge.len)
+                .unwrap_or_else(|| panic!("{:?} and {:?} aren't joined", pair[0], pair[1]));
+            cells.extend(&edge.cells);
+            cells.push(self.coord(pair[1]));
+        }
+        cells
+    }
+}
+
+pub(crate) fn to_graph<T, P>(
+    grid: &Grid<T>,
+    mut passable: P,
+    points_of_interest: &[Coord],
+) -> GridGraph
+where
+    P: FnMut(&T) -> bool,
+{
+    let open = grid
+        .indexed_iter()
+        .filter(|(_, item)| passable(item))
+        .map(|((row, col), _)| Coord::new(row, col))
+        .collect::<HashSet<_>>();
+    let open_neighbours = |coord: &Coord| {
+        grid.neighbours(coord, Connectivity::Four)
+            .filter(|next| open.contains(next))
+            .collect::<Vec<_>>()
+    };
+
+    // Anything that isn't the middle of a corridor is a node
+    let mut nodes = open
+        .iter()
+        .filter(|coord| open_neighbours(coord).len() != 2)
+        .chain(
+            points_of_interest
+                .iter()
+                .filter(|coord| open.contains(coord)),
+        )
+        .copied()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    nodes.sort_by_key(|coord| (coord.row, coord.col));
+    let index = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, coord)| (*coord, NodeId(i)))
+        .collect::<HashMap<_, _>>();
+
+    // Walk out of every node along every corridor until we bump into another node. Corridor cells only have the
+    // one way on, so there's nothing to decide on the way.
+    let mut edges = vec![Vec::new(); nodes.len()];
+    for (i, start) in nodes.iter().enumerate() {
+        for first in open_neighbours(start) {
+            let (mut prev, mut current) = (*start, first);
+            let mut cells = Vec::new();
+            while !index.contains_key(&current) {
+                cells.push(current);
+                let next = open_neighbours(&current)
+                    .into_iter()
+                    .find(|next| *next != prev)
+                    .unwrap();
+                (prev, current) = (current, next);
+            }
+            // A corridor that comes straight back round to where it started never helps a path
+            if current == *start {
+                continue;
+            }
+            edges[i].push(Edge {
+                to: index[&current],
+                len: cells.len() as u64 + 1,
+                cells,
+            });
+        }
+    }
+
+    GridGraph {
+        nodes,
+        index,
+        edges,
+    }
+}
@@ -0,0 +1,224 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::Hash;
+
+use grid::Grid;
+
+use super::{bfs, Connectivity, Coord, Direction, DistanceMap, GridExt, Renderable, Walk};
+
+// Anything that has cells sitting at positions we can step between. The dense `Grid`, the `SparseGrid` and the
+// grid views all implement this, which means the neighbour, ray and BFS helpers below only get written once.
+// A position that `cell` returns None for is outside the world, and the helpers never step onto it.
+// Nothing here cares how many dimensions there are, so the 3-D grids are `GridLike` too. They just move by a
+// different `Offset` and have their own `Direction` and `Connectivity`.
+pub trait GridLike: Sized {
+    type Cell;
+    type Pos: Copy + Eq + Hash + fmt::Debug;
+    // How far a single step moves. (rows, cols) on a flat grid.
+    type Offset: Copy + 'static;
+    type Direction: Heading<Offset = Self::Offset>;
+    type Connectivity: Neighbourhood<Offset = Self::Offset>;
+    // Something to remember a value per position. The dense grid uses another dense grid, others use a HashMap.
+    type Map<V>: PosMap<Self::Pos, V>;
+
+    fn cell(&self, pos: &Self::Pos) -> Option<&Self::Cell>;
+    // Moves `pos` by `offset`. This only fails if the position can't be represented at all, whether there's
+    // a cell there is up to `cell`.
+    fn step(&self, pos: &Self::Pos, offset: Self::Offset) -> Option<Self::Pos>;
+    fn new_map<V>(&self) -> Self::Map<V>;
+
+    // The neighbouring positions that are still inside the world
+    fn neighbours(
+        &self,
+        pos: &Self::Pos,
+        connectivity: Self::Connectivity,
+    ) -> impl Iterator<Item = Self::Pos> + '_ {
+        let pos = *pos;
+        connectivity
+            .offsets()
+            .iter()
+            .filter_map(move |offset| self.step(&pos, *offset))
+            .filter(|next| self.cell(next).is_some())
+    }
+
+    // Every cell you'd pass through walking from `from` towards `dir`, not counting `from` itself.
+    // Chain `.until(...)` onto it to stop at a wall, a taller tree, etc.
+    fn ray(&self, from: &Self::Pos, dir: &Self::Direction) -> Walk<'_, Self> {
+        let step = dir.offset();
+        Walk::new(self, self.step(from, step), step, None)
+    }
+
+    // Breadth first search out from every start at once. `can_step` gets the cell we're on, the cell we want to
+    // move to and the direction we're moving in, so things like pipes that only connect on certain sides work too.
+    fn bfs<P>(&self, starts: &[Self::Pos], can_step: P) -> DistanceMap<Self>
+    where
+        P: FnMut(&Self::Cell, &Self::Cell, Self::Direction) -> bool,
+    {
+        bfs::bfs(self, starts, can_step, &mut HashSet::new())
+    }
+
+    // Same as `bfs`, but `visited` keeps track of where we've been. Anything already in it is never stepped onto,
+    // so it can carry over from an earlier search. A `BitGrid` makes a very cheap visited set for dense grids.
+    fn bfs_with<P, S>(
+        &self,
+        starts: &[Self::Pos],
+        can_step: P,
+        visited: &mut S,
+    ) -> DistanceMap<Self>
+    where
+        P: FnMut(&Self::Cell, &Self::Cell, Self::Direction) -> bool,
+        S: PosSet<Self::Pos>,
+    {
+        bfs::bfs(self, starts, can_step, visited)
+    }
+
+    // Every cell we can spread to from the starts. `can_spread` gets the cell we're spreading from and the one
+    // we're spreading to.
+    fn flood_fill<P>(
+        &self,
+        starts: &[Self::Pos],
+        connectivity: Self::Connectivity,
+        can_spread: P,
+    ) -> HashSet<Self::Pos>
+    where
+        P: FnMut(&Self::Cell, &Self::Cell) -> bool,
+    {
+        let mut filled = HashSet::new();
+        bfs::flood_fill(self, starts, connectivity, can_spread, &mut filled);
+        filled
+    }
+
+    // Same as `flood_fill`, but fills into a set we hand it. Anything already in `filled` acts like a wall.
+    fn flood_fill_with<P, S>(
+        &self,
+        starts: &[Self::Pos],
+        connectivity: Self::Connectivity,
+        can_spread: P,
+        filled: &mut S,
+    ) where
+        P: FnMut(&Self::Cell, &Self::Cell) -> bool,
+        S: PosSet<Self::Pos>,
+    {
+        bfs::flood_fill(self, starts, connectivity, can_spread, filled)
+    }
+}
+
+// A way a search can move. `Direction` on a flat grid, `Direction3` in 3-D.
+pub trait Heading: Copy + 'static {
+    type Offset;
+    const ALL: &'static [Self];
+
+    fn offset(&self) -> Self::Offset;
+}
+
+// Which positions count as touching, as the offsets to get to each of them
+pub trait Neighbourhood: Copy {
+    type Offset: 'static;
+
+    fn offsets(&self) -> &'static [Self::Offset];
+}
+
+impl Heading for Direction {
+    type Offset = (isize, isize);
+    const ALL: &'static [Direction] = &Direction::ALL;
+
+    fn offset(&self) -> (isize, isize) {
+        Direction::offset(self)
+    }
+}
+
+impl Neighbourhood for Connectivity {
+    type Offset = (isize, isize);
+
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        Connectivity::offsets(self)
+    }
+}
+
+// Somewhere to keep track of which positions we've already been to
+pub trait PosSet<P> {
+    fn contains(&self, pos: &P) -> bool;
+    // Returns true if `pos` wasn't in the set yet
+    fn insert(&mut self, pos: P) -> bool;
+}
+
+impl<P> PosSet<P> for HashSet<P>
+where
+    P: Eq + Hash,
+{
+    fn contains(&self, pos: &P) -> bool {
+        HashSet::contains(self, pos)
+    }
+
+    fn insert(&mut self, pos: P) -> bool {
+        HashSet::insert(self, pos)
+    }
+}
+
+pub trait PosMap<P, V> {
+    fn get(&self, pos: &P) -> Option<&V>;
+    fn insert(&mut self, pos: P, value: V);
+    fn entries(&self) -> Box<dyn Iterator<Item = (P, &V)> + '_>;
+}
+
+impl<P, V> PosMap<P, V> for HashMap<P, V>
+where
+    P: Copy + Eq + Hash,
+{
+    fn get(&self, pos: &P) -> Option<&V> {
+        HashMap::get(self, pos)
+    }
+
+    fn insert(&mut self, pos: P, value: V) {
+        HashMap::insert(self, pos, value);
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (P, &V)> + '_> {
+        Box::new(self.iter().map(|(pos, value)| (*pos, value)))
+    }
+}
+
+impl<V> PosMap<Coord, V> for Grid<Option<V>> {
+    fn get(&self, pos: &Coord) -> Option<&V> {
+        self.get_at_coord(pos)?.as_ref()
+    }
+
+    // Panics if `pos` is off the grid, the same way indexing would
+    fn insert(&mut self, pos: Coord, value: V) {
+        self[(pos.row, pos.col)] = Some(value);
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (Coord, &V)> + '_> {
+        Box::new(
+            self.indexed_iter()
+                .filter_map(|((row, col), value)| Some((Coord::new(row, col), value.as_ref()?))),
+        )
+    }
+}
+
+impl<T> GridLike for Grid<T> {
+    type Cell = T;
+    type Pos = Coord;
+    type Map<V> = Grid<Option<V>>;
+    type Offset = (isize, isize);
+    type Direction = Direction;
+    type Connectivity = Connectivity;
+
+    fn cell(&self, pos: &Coord) -> Option<&T> {
+        self.get_at_coord(pos)
+    }
+
+    fn step(&self, pos: &Coord, offset: (isize, isize)) -> Option<Coord> {
+        pos.offset(offset)
+    }
+
+    fn new_map<V>(&self) -> Grid<Option<V>> {
+        Grid::new(self.rows(), self.cols())
+    }
+}
+
+impl<T> Renderable for Grid<T> {
+    fn extent(&self) -> (Coord, usize, usize) {
+        (Coord::new(0, 0), self.rows(), self.cols())
+    }
+}
@@ -0,0 +1,201 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use grid::Grid;
+
+use super::Coord;
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+
+    // The `#rrggbb` form, for SVG
+    pub fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+// A plain old bitmap. Every grid cell becomes a `scale` by `scale` square of pixels, which makes big inputs far
+// easier to look at than a terminal full of characters.
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Image {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    pub fn from_grid<T, P>(grid: &Grid<T>, palette: P, scale: usize) -> Self
+    where
+        P: Fn(&T) -> Rgb,
+    {
+        let mut image = Image::new(grid.cols() * scale, grid.rows() * scale, Rgb::BLACK);
+        for ((row, col), item) in grid.indexed_iter() {
+            image.fill_cell(&Coord::new(row, col), scale, palette(item));
+        }
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    // (x, y) with the origin in the top left, like every image format expects
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.pixels[y * self.width + x])
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        if x < self.width && y < self.height {
+            self.pixels[y * self.width + x] = color;
+        }
+    }
+
+    // Paints over the square a grid cell was drawn into. Handy for highlighting a path after the fact.
+    pub fn fill_cell(&mut self, coord: &Coord, scale: usize, color: Rgb) {
+        for y in coord.row * scale..(coord.row + 1) * scale {
+            for x in coord.col * scale..(coord.col + 1) * scale {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    // Just the red, green and blue bytes of every pixel one after the other
+    pub fn rgb_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|Rgb(r, g, b)| [*r, *g, *b])
+            .collect()
+    }
+
+    // Binary PPM. It's about the simplest image format there is and most image viewers open it.
+    pub fn write_ppm<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        out.write_all(&self.rgb_bytes())
+    }
+
+    pub fn write_png<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(io::Error::other)?;
+        writer
+            .write_image_data(&self.rgb_bytes())
+            .map_err(io::Error::other)
+    }
+
+    // Picks PNG or PPM from the file extension. The extension gets checked before the file is opened, so a typo
+    // doesn't wipe out whatever was already there.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => self.write_png(&mut BufWriter::new(File::create(path)?)),
+            Some("ppm") => self.write_ppm(&mut BufWriter::new(File::create(path)?)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Don't know how to save {}, use .png or .ppm",
+                    path.display()
+                ),
+            )),
+        }
+    }
+}
+
+// An SVG of the grid, drawn as one square per cell. On top of the cells we can draw lines through the middle of
+// cells, which is a nice way to show a pipe loop or the route a beam took.
+pub struct Svg {
+    rows: usize,
+    cols: usize,
+    scale: usize,
+    cells: Vec<Rgb>,
+    polylines: Vec<(Vec<Coord>, Rgb, f64)>,
+}
+
+impl Svg {
+    pub fn from_grid<T, P>(grid: &Grid<T>, palette: P, scale: usize) -> Self
+    where
+        P: Fn(&T) -> Rgb,
+    {
+        Svg {
+            rows: grid.rows(),
+            cols: grid.cols(),
+            scale,
+            cells: grid.iter_rows().flatten().map(palette).collect(),
+            polylines: Vec::new(),
+        }
+    }
+
+    // Draws a line through the centres of `coords`, in order. `width` is in pixels.
+    pub fn polyline(mut self, coords: &[Coord], color: Rgb, width: f64) -> Self {
+        self.polylines.push((coords.to_vec(), color, width));
+        self
+    }
+
+    // Same as `polyline`, but joins the last cell back up to the first
+    pub fn polygon(self, coords: &[Coord], color: Rgb, width: f64) -> Self {
+        let mut closed = coords.to_vec();
+        closed.extend(coords.first());
+        self.polyline(&closed, color, width)
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let (width, height) = (self.cols * self.scale, self.rows * self.scale);
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" shape-rendering="crispEdges">"#
+        )?;
+        for (i, color) in self.cells.iter().enumerate() {
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{s}" height="{s}" fill="{}"/>"#,
+                (i % self.cols) * self.scale,
+                (i / self.cols) * self.scale,
+                color.hex(),
+                s = self.scale,
+            )?;
+        }
+        let centre = |coord: &Coord| {
+            let half = self.scale as f64 / 2.0;
+            format!(
+                "{},{}",
+                (coord.col * self.scale) as f64 + half,
+                (coord.row * self.scale) as f64 + half
+            )
+        };
+        for (coords, color, line_width) in &self.polylines {
+            let points = coords.iter().map(centre).collect::<Vec<_>>().join(" ");
+            writeln!(
+                out,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round" stroke-linecap="round" shape-rendering="auto"/>"#,
+                points,
+                color.hex(),
+                line_width
+            )?;
+        }
+        writeln!(out, "</svg>")
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write(&mut BufWriter::new(File::create(path)?))
+    }
+}
@@ -0,0 +1,228 @@
+use std::any::Any;
+use std::marker::PhantomData;
+
+use colored::Color;
+use grid::Grid;
+
+use super::{Coord, GridExt, Renderer};
+
+// A handle to one overlay of a `LayeredGrid`. It remembers the layer's type, so looking a layer up hands back a
+// `Grid<V>` without any casting on our end.
+pub struct LayerId<V> {
+    index: usize,
+    layer_type: PhantomData<fn() -> V>,
+}
+
+// Derives would want `V: Clone` and `V: Copy`, which a handle doesn't need
+impl<V> Clone for LayerId<V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for LayerId<V> {}
+
+// A map that doesn't change (the puzzle input) with any number of state grids laid over the top of it, like which
+// cells are energized, visited or on the path. Every layer is the same size as the base, so a coordinate means
+// the same thing in all of them and one bounds check covers the lot.
+pub struct LayeredGrid<T> {
+    base: Grid<T>,
+    layers: Vec<Box<dyn Any>>,
+}
+
+impl<T> LayeredGrid<T> {
+    pub fn new(base: Grid<T>) -> Self {
+        LayeredGrid {
+            base,
+            layers: Vec::new(),
+        }
+    }
+
+    pub fn base(&self) -> &Grid<T> {
+        &self.base
+    }
+
+    pub fn into_base(self) -> Grid<T> {
+        self.base
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.base.size()
+    }
+
+    pub fn contains(&self, coord: &Coord) -> bool {
+        coord.row < self.base.rows() && coord.col < self.base.cols()
+    }
+
+    // Adds a layer with every cell set to `fill`
+    pub fn add_layer<V>(&mut self, fill: V) -> LayerId<V>
+    where
+        V: Clone + 'static,
+    {
+        let (rows, cols) = self.size();
+        self.push_layer(Grid::init(rows, cols, fill))
+    }
+
+    // Adds a layer that's already been filled in. Panics if it isn't the same size as the base.
+    pub fn add_layer_from<V>(&mut self, layer: Grid<V>) -> LayerId<V>
+    where
+        V: 'static,
+    {
+        assert_eq!(
+            layer.size(),
+            self.size(),
+            "Layers have to be the same size as the base"
+        );
+        self.push_layer(layer)
+    }
+
+    fn push_layer<V>(&mut self, layer: Grid<V>) -> LayerId<V>
+    where
+        V: 'static,
+    {
+        self.layers.push(Box::new(layer));
+        LayerId {
+            index: self.layers.len() - 1,
+            layer_type: PhantomData,
+        }
+    }
+
+    pub fn layer<V>(&self, id: LayerId<V>) -> &Grid<V>
+    where
+        V: 'static,
+    {
+        self.layers
+            .get(id.index)
+            .and_then(|layer| layer.downcast_ref())
+            .expect("LayerId is from a different LayeredGrid")
+    }
+
+    pub fn layer_mut<V>(&mut self, id: LayerId<V>) -> &mut Grid<V>
+    where
+        V: 'static,
+    {
+        self.layers
+            .get_mut(id.index)
+            .and_then(|layer| layer.downcast_mut())
+            .expect("LayerId is from a different LayeredGrid")
+    }
+
+    pub fn cell(&self, coord: &Coord) -> Option<&T> {
+        self.base.get_at_coord(coord)
+    }
+
+    pub fn get<V>(&self, id: LayerId<V>, coord: &Coord) -> Option<&V>
+    where
+        V: 'static,
+    {
+        self.layer(id).get_at_coord(coord)
+    }
+
+    pub fn get_mut<V>(&mut self, id: LayerId<V>, coord: &Coord) -> Option<&mut V>
+    where
+        V: 'static,
+    {
+        self.layer_mut(id).get_at_coord_mut(coord)
+    }
+
+    // Resets every cell of a layer to `fill`, for running the simulation again from a different start
+    pub fn reset_layer<V>(&mut self, id: LayerId<V>, fill: V)
+    where
+        V: Clone + 'static,
+    {
+        self.layer_mut(id).fill(fill)
+    }
+
+    // Starts drawing the grid with `format_cell` for the base. Add overlays on top with `LayeredRenderer::overlay`.
+    pub fn renderer<'a, F>(&'a self, format_cell: F) -> LayeredRenderer<'a, T>
+    where
+        F: Fn(&T) -> String + 'a,
+    {
+        LayeredRenderer {
+            grid: self,
+            format_cell: Box::new(format_cell),
+            overlays: Vec::new(),
+            spacing: 0,
+            rulers: false,
+            color: colored::control::SHOULD_COLORIZE.should_colorize(),
+        }
+    }
+}
+
+type OverlayFn<'a> = Box<dyn Fn(&Coord) -> Option<(String, Option<Color>)> + 'a>;
+
+// Draws the base layer with overlays on top. For each cell the last overlay added that has something to say
+// wins, and if none of them do we fall back to the base symbol.
+pub struct LayeredRenderer<'a, T> {
+    grid: &'a LayeredGrid<T>,
+    format_cell: Box<dyn Fn(&T) -> String + 'a>,
+    overlays: Vec<OverlayFn<'a>>,
+    spacing: usize,
+    rulers: bool,
+    color: bool,
+}
+
+impl<'a, T> LayeredRenderer<'a, T> {
+    // `draw` returns what to show for a layer's cell, or None to let whatever is underneath show through.
+    // `color` is the text colour for the cells it draws.
+    pub fn overlay<V, F>(mut self, id: LayerId<V>, draw: F, color: Option<Color>) -> Self
+    where
+        V: 'static,
+        F: Fn(&V) -> Option<String> + 'a,
+    {
+        let layer = self.grid.layer(id);
+        self.overlays.push(Box::new(move |coord| {
+            let text = draw(layer.get_at_coord(coord)?)?;
+            Some((text, color))
+        }));
+        self
+    }
+
+    pub fn spacing(mut self, spacing: usize) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    pub fn rulers(mut self, rulers: bool) -> Self {
+        self.rulers = rulers;
+        self
+    }
+
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    // Flattens the layers down into what each cell will look like, then lets `Renderer` do the actual drawing
+    pub fn render(&self) -> String {
+        let (rows, cols) = self.grid.size();
+        let cells = self
+            .grid
+            .base
+            .indexed_iter()
+            .map(|((row, col), item)| {
+                let coord = Coord::new(row, col);
+                self.overlays
+                    .iter()
+                    .rev()
+                    .find_map(|overlay| overlay(&coord))
+                    .unwrap_or_else(|| ((self.format_cell)(item), None))
+            })
+            .collect::<Vec<_>>();
+        let flat = if rows == 0 {
+            Grid::new(0, 0)
+        } else {
+            Grid::from_vec(cells, cols)
+        };
+        Renderer::new(|(text, _): &(String, Option<Color>)| text.clone())
+            .style(|_, (_, color)| *color)
+            .spacing(self.spacing)
+            .rulers(self.rulers)
+            .color(self.color)
+            .render(&flat)
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.render())
+    }
+}
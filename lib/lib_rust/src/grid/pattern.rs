@@ -0,0 +1,235 @@
+use grid::Grid;
+
+use super::{Coord, GridExt, Lines, Transform, Walk};
+
+// Somewhere the pattern fits. `transform` is what was done to the pattern before placing it, so the matched
+// cells are `pattern.view(transform)` laid down with its top left corner on `top_left`.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct PatternMatch {
+    pub top_left: Coord,
+    pub transform: Transform,
+}
+
+// A word found by `find_word`. It starts on `start` and carries on one `step` at a time for `len` cells.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct WordMatch {
+    pub start: Coord,
+    pub step: (isize, isize),
+    pub len: usize,
+}
+
+impl WordMatch {
+    pub fn cells(&self) -> Vec<Coord> {
+        let mut cells = vec![self.start];
+        for _ in 1..self.len {
+            cells.push(cells.last().unwrap().offset(self.step).unwrap());
+        }
+        cells
+    }
+}
+
+pub(crate) fn find_pattern<T>(
+    grid: &Grid<T>,
+    pattern: &Grid<T>,
+    wildcard: Option<&T>,
+    transforms: &[Transform],
+) -> Vec<PatternMatch>
+where
+    T: Clone + PartialEq,
+{
+    // An empty pattern would otherwise "fit" everywhere
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    let mut seen: Vec<Grid<T>> = Vec::new();
+    let mut matches = Vec::new();
+    for transform in transforms {
+        // A symmetric pattern looks the same under more than one transform. We only search for each distinct
+        // shape once, otherwise every match would get counted several times.
+        let placed = pattern.view(*transform).to_grid();
+        if seen
+            .iter()
+            .any(|other| other.size() == placed.size() && other.iter().eq(placed.iter()))
+        {
+            continue;
+        }
+        let top_lefts = if placed.rows() == 1
+            && !wildcard.is_some_and(|w| placed.iter().any(|item| item == w))
+        {
+            find_in_rows(grid, &placed)
+        } else {
+            find_anywhere(grid, &placed, wildcard)
+        };
+        matches.extend(top_lefts.into_iter().map(|top_left| PatternMatch {
+            top_left,
+            transform: *transform,
+        }));
+        seen.push(placed);
+    }
+    matches
+}
+
+// The fast path for a single row with no wildcards. Each row of the grid gets searched with KMP, so we never
+// look at a cell more than twice.
+fn find_in_rows<T>(grid: &Grid<T>, pattern: &Grid<T>) -> Vec<Coord>
+where
+    T: PartialEq,
+{
+    let needle = pattern.iter().collect::<Vec<_>>();
+    (0..grid.rows())
+        .flat_map(|row| {
+            let haystack = grid.iter_row(row).collect::<Vec<_>>();
+            kmp(&haystack, &needle)
+                .into_iter()
+                .map(move |col| Coord::new(row, col))
+        })
+        .collect()
+}
+
+// Tries the pattern at every position, giving up on each one as soon as a cell doesn't match
+fn find_anywhere<T>(grid: &Grid<T>, pattern: &Grid<T>, wildcard: Option<&T>) -> Vec<Coord>
+where
+    T: PartialEq,
+{
+    let (rows, cols) = pattern.size();
+    if rows > grid.rows() || cols > grid.cols() {
+        return Vec::new();
+    }
+    let cells = pattern
+        .indexed_iter()
+        .filter(|(_, item)| wildcard != Some(*item))
+        .collect::<Vec<_>>();
+    (0..=grid.rows() - rows)
+        .flat_map(|row| (0..=grid.cols() - cols).map(move |col| Coord::new(row, col)))
+        .filter(|top_left| {
+            cells.iter().all(|((row, col), item)| {
+                grid.get(top_left.row + row, top_left.col + col) == Some(*item)
+            })
+        })
+        .collect()
+}
+
+// Word search: every place `word` can be read in a straight line, in any of the 8 directions. Each line of the
+// grid is searched for the word and for the word backwards, which covers both ways along it.
+pub(crate) fn find_word<T>(grid: &Grid<T>, word: &[T]) -> Vec<WordMatch>
+where
+    T: PartialEq,
+{
+    let (rows, cols) = grid.size();
+    if word.is_empty() || rows == 0 || cols == 0 {
+        return Vec::new();
+    }
+    let forwards = word.iter().collect::<Vec<_>>();
+    let backwards = word.iter().rev().collect::<Vec<_>>();
+    let is_palindrome = forwards == backwards;
+    // A single cell reads the same in every direction, so searching the rows alone finds each one exactly once
+    let all_lines: &[Lines] = if word.len() == 1 {
+        &[Lines::Rows]
+    } else {
+        &[
+            Lines::Rows,
+            Lines::Columns,
+            Lines::Diagonals,
+            Lines::AntiDiagonals,
+        ]
+    };
+    let mut matches = Vec::new();
+    for lines in all_lines {
+        let (d_row, d_col) = lines.step();
+        for start in lines.starts(rows, cols) {
+            let (coords, line): (Vec<_>, Vec<_>) =
+                Walk::new(grid, Some(start), (d_row, d_col), None).unzip();
+            for i in kmp(&line, &forwards) {
+                matches.push(WordMatch {
+                    start: coords[i],
+                    step: (d_row, d_col),
+                    len: word.len(),
+                });
+            }
+            // A palindrome reads the same both ways, so the backwards search would only find the same cells again
+            if is_palindrome {
+                continue;
+            }
+            for i in kmp(&line, &backwards) {
+                matches.push(WordMatch {
+                    start: coords[i + word.len() - 1],
+                    step: (-d_row, -d_col),
+                    len: word.len(),
+                });
+            }
+        }
+    }
+    matches
+}
+
+// Knuth-Morris-Pratt. Returns the start of every occurrence of `needle` in `haystack`, overlapping ones included.
+fn kmp<T>(haystack: &[&T], needle: &[&T]) -> Vec<usize>
+where
+    T: PartialEq,
+{
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return Vec::new();
+    }
+    // failure[i] is the length of the longest proper prefix of needle[..=i] that's also a suffix of it
+    let mut failure = vec![0; needle.len()];
+    let mut len = 0;
+    for i in 1..needle.len() {
+        while len > 0 && needle[i] != needle[len] {
+            len = failure[len - 1];
+        }
+        if needle[i] == needle[len] {
+            len += 1;
+        }
+        failure[i] = len;
+    }
+
+    let mut found = Vec::new();
+    let mut len = 0;
+    for (i, item) in haystack.iter().enumerate() {
+        while len > 0 && *item != needle[len] {
+            len = failure[len - 1];
+        }
+        if *item == needle[len] {
+            len += 1;
+        }
+        if len == needle.len() {
+            found.push(i + 1 - len);
+            len = failure[len - 1];
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A single letter reads the same in all 8 directions, so it should only be found once per cell
+    #[test]
+    fn single_letter_word_once_per_cell() {
+        let grid = Grid::from_vec("XMXAXS".chars().collect(), 3);
+        let matches = grid.find_word(&['X']);
+
+        let mut starts = matches.iter().map(|m| m.start).collect::<Vec<_>>();
+        starts.sort_by_key(|coord| (coord.row, coord.col));
+        assert_eq!(
+            starts,
+            vec![Coord::new(0, 0), Coord::new(0, 2), Coord::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn two_letter_words_found_once() {
+        let grid = Grid::from_vec("XMAS".chars().collect(), 4);
+        let word = "XM".chars().collect::<Vec<_>>();
+        assert_eq!(grid.find_word(&word).len(), 1);
+        let word = "MA".chars().collect::<Vec<_>>();
+        assert_eq!(grid.find_word(&word).len(), 1);
+    }
+
+    #[test]
+    fn empty_pattern_matches_nothing() {
+        let grid = Grid::from_vec("XMAS".chars().collect(), 2);
+        assert!(grid.find_pattern(&Grid::new(0, 0), None).is_empty());
+    }
+}
@@ -0,0 +1,296 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use grid::Grid;
+
+use super::{Coord, Image, Rgb};
+
+type PaletteFn<'a, T> = Box<dyn Fn(&T) -> Rgb + 'a>;
+type TextFn<'a, T> = Box<dyn Fn(&T) -> String + 'a>;
+
+// Records a simulation one grid at a time so we can watch it back afterwards, either as an animated GIF or as an
+// asciinema recording (`asciinema play out.cast`). Call `capture` every time something interesting happens, then
+// `save` at the end.
+//
+// Frames are kept as copies of the grid and only drawn when we save, so capturing is cheap.
+pub struct Recorder<'a, T> {
+    palette: PaletteFn<'a, T>,
+    text: TextFn<'a, T>,
+    frames: Vec<(Grid<T>, HashSet<Coord>)>,
+    scale: usize,
+    fps: u32,
+    dedup: bool,
+    highlight: Rgb,
+    output: Option<PathBuf>,
+}
+
+impl<'a, T> Recorder<'a, T>
+where
+    T: Clone + Eq,
+{
+    // `palette` colours cells in the GIF, `text` is how a cell shows up in the asciinema recording
+    pub fn new<P, F>(palette: P, text: F) -> Self
+    where
+        P: Fn(&T) -> Rgb + 'a,
+        F: Fn(&T) -> String + 'a,
+    {
+        Recorder {
+            palette: Box::new(palette),
+            text: Box::new(text),
+            frames: Vec::new(),
+            scale: 4,
+            fps: 10,
+            dedup: false,
+            highlight: Rgb(255, 215, 0),
+            output: None,
+        }
+    }
+
+    // Only hands back a recorder if the program was run with `--record <path>` (or `--record=<path>`), and that
+    // recorder will save itself to the path when `finish` is called. That way a solution can always record and
+    // it only costs anything when we ask for it, e.g. `cargo run -- --record out.gif`.
+    // The path gets checked straight away, so `--record out.mp4` is an error before the simulation runs rather
+    // than after it.
+    pub fn from_args<P, F>(palette: P, text: F) -> io::Result<Option<Self>>
+    where
+        P: Fn(&T) -> Rgb + 'a,
+        F: Fn(&T) -> String + 'a,
+    {
+        let Some(output) = record_path(std::env::args().skip(1)) else {
+            return Ok(None);
+        };
+        Format::of(&output)?;
+        Ok(Some(Recorder {
+            output: Some(output),
+            ..Recorder::new(palette, text)
+        }))
+    }
+
+    // How many pixels wide each cell is in the GIF. Panics on 0, which would leave nothing to see.
+    pub fn scale(mut self, scale: usize) -> Self {
+        assert!(scale > 0, "A GIF needs at least one pixel per cell");
+        self.scale = scale;
+        self
+    }
+
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.fps = fps.max(1);
+        self
+    }
+
+    // Skip any frame that looks exactly the same as the one before it
+    pub fn dedup(mut self, dedup: bool) -> Self {
+        self.dedup = dedup;
+        self
+    }
+
+    // The colour highlighted cells are painted in the GIF. In the recording they're shown in reverse video.
+    pub fn highlight_color(mut self, color: Rgb) -> Self {
+        self.highlight = color;
+        self
+    }
+
+    pub fn capture(&mut self, grid: &Grid<T>) {
+        self.capture_highlighted(grid, []);
+    }
+
+    pub fn capture_highlighted<I>(&mut self, grid: &Grid<T>, highlights: I)
+    where
+        I: IntoIterator<Item = Coord>,
+    {
+        let highlights = highlights.into_iter().collect();
+        if self.dedup {
+            if let Some((last_grid, last_highlights)) = self.frames.last() {
+                if last_grid == grid && *last_highlights == highlights {
+                    return;
+                }
+            }
+        }
+        self.frames.push((grid.clone(), highlights));
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    fn draw(&self, grid: &Grid<T>, highlights: &HashSet<Coord>) -> Image {
+        let mut image = Image::from_grid(grid, &self.palette, self.scale);
+        for coord in highlights {
+            image.fill_cell(coord, self.scale, self.highlight);
+        }
+        image
+    }
+
+    pub fn write_gif<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let Some((first, _)) = self.frames.first() else {
+            return Ok(());
+        };
+        // A GIF is one size all the way through, so a grid that grew or shrank part way can't go in it. The
+        // asciicast doesn't mind, it just redraws the screen.
+        if let Some((i, (grid, _))) = self
+            .frames
+            .iter()
+            .enumerate()
+            .find(|(_, (grid, _))| grid.size() != first.size())
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Frame {} is {}x{} but the first frame is {}x{}, every frame of a GIF has to be the same size",
+                    i,
+                    grid.rows(),
+                    grid.cols(),
+                    first.rows(),
+                    first.cols()
+                ),
+            ));
+        }
+        let (width, height) = (first.cols() * self.scale, first.rows() * self.scale);
+        let too_big = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}x{} is too big for a GIF", width, height),
+            )
+        };
+        let (width, height) = (
+            u16::try_from(width).map_err(|_| too_big())?,
+            u16::try_from(height).map_err(|_| too_big())?,
+        );
+
+        let mut encoder = gif::Encoder::new(out, width, height, &[]).map_err(io::Error::other)?;
+        encoder
+            .set_repeat(gif::Repeat::Infinite)
+            .map_err(io::Error::other)?;
+        // GIF delays are in hundredths of a second
+        let delay = (100 / self.fps).max(1) as u16;
+        for (grid, highlights) in &self.frames {
+            let image = self.draw(grid, highlights);
+            let mut frame = gif::Frame::from_rgb_speed(width, height, &image.rgb_bytes(), 10);
+            frame.delay = delay;
+            encoder.write_frame(&frame).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+
+    fn draw_text(&self, grid: &Grid<T>, highlights: &HashSet<Coord>) -> Vec<String> {
+        (0..grid.rows())
+            .map(|row| {
+                (0..grid.cols())
+                    .map(|col| {
+                        let text = (self.text)(grid.get(row, col).unwrap());
+                        if highlights.contains(&Coord::new(row, col)) {
+                            format!("\x1b[7m{}\x1b[0m", text)
+                        } else {
+                            text
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // An asciicast v2 file: a JSON header line, then one `[time, "o", text]` line per frame
+    pub fn write_cast<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let Some((first, _)) = self.frames.first() else {
+            return Ok(());
+        };
+        let width = (0..first.cols())
+            .map(|col| (self.text)(first.get(0, col).unwrap()).chars().count())
+            .sum::<usize>();
+        writeln!(
+            out,
+            r#"{{"version": 2, "width": {}, "height": {}}}"#,
+            width.max(1),
+            first.rows().max(1)
+        )?;
+        for (i, (grid, highlights)) in self.frames.iter().enumerate() {
+            // Jump back to the top left and draw over the last frame
+            let screen = format!(
+                "\x1b[H\x1b[2J{}",
+                self.draw_text(grid, highlights).join("\r\n")
+            );
+            writeln!(
+                out,
+                r#"[{:.3}, "o", "{}"]"#,
+                i as f64 / self.fps as f64,
+                json_escape(&screen)
+            )?;
+        }
+        Ok(())
+    }
+
+    // Picks GIF or asciicast from the file extension. The extension gets checked before the file is opened, so a
+    // typo doesn't wipe out whatever was already there.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = Format::of(path)?;
+        let mut out = BufWriter::new(File::create(path)?);
+        match format {
+            Format::Gif => self.write_gif(&mut out),
+            Format::Cast => self.write_cast(&mut out),
+        }
+    }
+
+    // Saves to the `--record` path if there was one
+    pub fn finish(&self) -> io::Result<()> {
+        match &self.output {
+            Some(path) => self.save(path),
+            None => Ok(()),
+        }
+    }
+}
+
+enum Format {
+    Gif,
+    Cast,
+}
+
+impl Format {
+    fn of(path: &Path) -> io::Result<Format> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("gif") => Ok(Format::Gif),
+            Some("cast") => Ok(Format::Cast),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Don't know how to save {}, use .gif or .cast",
+                    path.display()
+                ),
+            )),
+        }
+    }
+}
+
+fn record_path<I: Iterator<Item = String>>(mut args: I) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == "--record" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--record=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
+
+fn json_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
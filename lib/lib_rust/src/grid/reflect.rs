@@ -0,0 +1,88 @@
+use grid::Grid;
+
+use super::{Coord, GridExt, Transform};
+
+// Where a mirror line sits. `Horizontal(i)` is a horizontal line with `i` rows above it, `Vertical(i)` is a
+// vertical line with `i` columns to its left. A line right on the edge of the grid doesn't count.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Axis {
+    Horizontal(usize),
+    Vertical(usize),
+}
+
+// A mirror line along with every pair of cells that don't match across it
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Reflection {
+    pub axis: Axis,
+    pub mismatches: Vec<(Coord, Coord)>,
+}
+
+pub(crate) fn reflection_axes<T>(grid: &Grid<T>, tolerance: usize) -> Vec<Reflection>
+where
+    T: PartialEq,
+{
+    let (rows, cols) = grid.size();
+    let horizontal = (1..rows).map(Axis::Horizontal);
+    let vertical = (1..cols).map(Axis::Vertical);
+
+    horizontal
+        .chain(vertical)
+        .filter_map(|axis| {
+            let mismatches = mismatches(grid, axis, tolerance)?;
+            (mismatches.len() == tolerance).then_some(Reflection { axis, mismatches })
+        })
+        .collect()
+}
+
+// Every pair of mismatched cells across `axis`. We bail out with None as soon as there are more than `tolerance`,
+// since at that point the line is no good to us anyway.
+fn mismatches<T>(grid: &Grid<T>, axis: Axis, tolerance: usize) -> Option<Vec<(Coord, Coord)>>
+where
+    T: PartialEq,
+{
+    let (rows, cols) = grid.size();
+    let mut mismatches = Vec::new();
+    let pairs: Box<dyn Iterator<Item = (Coord, Coord)>> = match axis {
+        Axis::Horizontal(i) => {
+            let reach = i.min(rows - i);
+            Box::new((0..reach).flat_map(move |d| {
+                (0..cols).map(move |col| (Coord::new(i - 1 - d, col), Coord::new(i + d, col)))
+            }))
+        }
+        Axis::Vertical(i) => {
+            let reach = i.min(cols - i);
+            Box::new((0..reach).flat_map(move |d| {
+                (0..rows).map(move |row| (Coord::new(row, i - 1 - d), Coord::new(row, i + d)))
+            }))
+        }
+    };
+    for (a, b) in pairs {
+        if grid.get_at_coord(&a) != grid.get_at_coord(&b) {
+            mismatches.push((a, b));
+            if mismatches.len() > tolerance {
+                return None;
+            }
+        }
+    }
+    Some(mismatches)
+}
+
+pub(crate) fn rotational_symmetries<T>(grid: &Grid<T>) -> Vec<Transform>
+where
+    T: PartialEq,
+{
+    [
+        Transform::RotateRight,
+        Transform::RotateHalf,
+        Transform::RotateLeft,
+    ]
+    .into_iter()
+    .filter(|transform| {
+        let view = grid.view(*transform);
+        view.size() == grid.size()
+            && grid
+                .indexed_iter()
+                .all(|((row, col), item)| view.get(row, col) == Some(item))
+    })
+    .collect()
+}
@@ -0,0 +1,179 @@
+use std::collections::HashSet;
+
+use grid::Grid;
+
+use super::{Connectivity, Coord, GridExt, GridLike, PosSet};
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default, PartialOrd, Ord)]
+pub struct RegionId(pub usize);
+
+// A group of cells that are all connected to each other (orthogonally) and all belong together
+#[derive(Debug, Clone)]
+pub struct Region {
+    pub id: RegionId,
+    pub cells: HashSet<Coord>,
+}
+
+impl Region {
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    // The number of cell edges that touch something outside the region (or the edge of the grid)
+    pub fn perimeter(&self) -> usize {
+        self.cells
+            .iter()
+            .map(|cell| {
+                Connectivity::Four
+                    .offsets()
+                    .iter()
+                    .filter(|offset| !self.contains_offset(cell, **offset))
+                    .count()
+            })
+            .sum()
+    }
+
+    // The number of straight sides around the region, holes included. A shape has as many sides as it has corners,
+    // so that's what we count. Each cell checks its four corners for either an outside corner (neither neighbour is
+    // in the region) or an inside corner (both neighbours are, but the diagonal between them isn't).
+    pub fn sides(&self) -> usize {
+        let corners = [(-1, 0, 0, 1), (0, 1, 1, 0), (1, 0, 0, -1), (0, -1, -1, 0)];
+        self.cells
+            .iter()
+            .map(|cell| {
+                corners
+                    .iter()
+                    .filter(|(a_row, a_col, b_row, b_col)| {
+                        let a = self.contains_offset(cell, (*a_row, *a_col));
+                        let b = self.contains_offset(cell, (*b_row, *b_col));
+                        let diagonal = self.contains_offset(cell, (a_row + b_row, a_col + b_col));
+                        (!a && !b) || (a && b && !diagonal)
+                    })
+                    .count()
+            })
+            .sum()
+    }
+
+    // The top left and bottom right corners of the smallest box that fits the whole region
+    pub fn bounding_box(&self) -> (Coord, Coord) {
+        let min_row = self.cells.iter().map(|cell| cell.row).min().unwrap();
+        let max_row = self.cells.iter().map(|cell| cell.row).max().unwrap();
+        let min_col = self.cells.iter().map(|cell| cell.col).min().unwrap();
+        let max_col = self.cells.iter().map(|cell| cell.col).max().unwrap();
+        (Coord::new(min_row, min_col), Coord::new(max_row, max_col))
+    }
+
+    fn contains_offset(&self, cell: &Coord, offset: (isize, isize)) -> bool {
+        cell.offset(offset)
+            .is_some_and(|neighbour| self.cells.contains(&neighbour))
+    }
+}
+
+// Every region in a grid. `labels` says which region each cell belongs to, and `regions[id.0]` is that region.
+pub struct Regions {
+    pub labels: Grid<RegionId>,
+    pub regions: Vec<Region>,
+}
+
+impl Regions {
+    pub fn region_at(&self, coord: &Coord) -> Option<&Region> {
+        self.regions.get(self.labels.get_at_coord(coord)?.0)
+    }
+}
+
+// Labels cells as the flood fill reaches them. Anything already labelled counts as filled, so a region can't
+// spread into one we made earlier. That matters when `same_region` isn't symmetric: with `|a, b| a >= b` the fill
+// from a low cell can't reach a higher neighbour, but the higher one's fill can reach back, and without this the
+// low cell would end up in both regions.
+struct Labeller<'a> {
+    labels: &'a mut Grid<Option<RegionId>>,
+    id: RegionId,
+    cells: HashSet<Coord>,
+}
+
+impl PosSet<Coord> for Labeller<'_> {
+    fn contains(&self, pos: &Coord) -> bool {
+        self.labels.get_at_coord(pos).is_some_and(Option::is_some)
+    }
+
+    fn insert(&mut self, pos: Coord) -> bool {
+        match self.labels.get_at_coord_mut(&pos) {
+            Some(label @ None) => {
+                *label = Some(self.id);
+                self.cells.insert(pos);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+pub(crate) fn regions<T, F>(grid: &Grid<T>, mut same_region: F) -> Regions
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut labels: Grid<Option<RegionId>> = Grid::new(grid.rows(), grid.cols());
+    let mut regions = Vec::new();
+
+    for row in 0..grid.rows() {
+        for col in 0..grid.cols() {
+            let start = Coord::new(row, col);
+            if labels.get_at_coord(&start).unwrap().is_some() {
+                continue;
+            }
+            let mut labeller = Labeller {
+                labels: &mut labels,
+                id: RegionId(regions.len()),
+                cells: HashSet::new(),
+            };
+            grid.flood_fill_with(
+                &[start],
+                Connectivity::Four,
+                &mut same_region,
+                &mut labeller,
+            );
+            regions.push(Region {
+                id: labeller.id,
+                cells: labeller.cells,
+            });
+        }
+    }
+
+    Regions {
+        labels: Grid::from_vec(
+            labels.into_vec().into_iter().map(Option::unwrap).collect(),
+            grid.cols(),
+        ),
+        regions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // With a one-way `same_region` the higher cell's fill can reach back into the lower one. Every cell still has
+    // to end up in exactly one region.
+    #[test]
+    fn asymmetric_same_region_partitions_the_grid() {
+        let grid = Grid::from_vec(vec![1, 2, 3, 1, 5, 1], 3);
+        let regions = grid.regions(|a, b| a >= b);
+
+        let total = regions.regions.iter().map(Region::area).sum::<usize>();
+        assert_eq!(total, 6);
+        for region in &regions.regions {
+            for cell in &region.cells {
+                assert_eq!(regions.labels.get_at_coord(cell), Some(&region.id));
+            }
+        }
+    }
+
+    #[test]
+    fn one_way_neighbours_stay_apart() {
+        let grid = Grid::from_vec(vec![1, 2], 2);
+        let regions = grid.regions(|a, b| a >= b);
+
+        assert_eq!(regions.regions.len(), 2);
+        assert!(regions.regions.iter().all(|region| region.area() == 1));
+    }
+}
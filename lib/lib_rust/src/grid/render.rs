@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::io;
+
+use colored::{Color, Colorize};
+
+use super::{Coord, GridLike};
+
+type FormatFn<'a, T> = Box<dyn Fn(&T, usize) -> String + 'a>;
+type StyleFn<'a, T, P> = Box<dyn Fn(&P, &T) -> Option<Color> + 'a>;
+
+// Anything with a rectangle of cells the `Renderer` can draw. `extent` is the top left position and how many rows
+// and columns to draw from there, and the renderer steps across from that corner to reach the rest. For a sparse
+// grid that's its bounding box, for a view it's the view.
+pub trait Renderable: GridLike<Offset = (isize, isize)> {
+    fn extent(&self) -> (Self::Pos, usize, usize);
+}
+
+// Turns a grid into text. By default each cell is printed with `{:?}` and nothing else, like `GridDisplay::print`,
+// but you can swap in your own formatter, add rulers along the top and left, colour cells, and highlight a set of
+// coordinates (a path, a region, a beam...). It draws anything `Renderable`, and `P` is the kind of position
+// that grid uses, which `render` and friends work out for you.
+// Rulers count from the top left of whatever is drawn, so on a sparse grid they start at 0 wherever that is.
+// Colour follows the `colored` crate, so it switches itself off when stdout isn't a terminal or NO_COLOR is set.
+// `color(false)` switches it off no matter what.
+pub struct Renderer<'a, T, P = Coord> {
+    // Gets the cell and the width to pad it out to
+    format_cell: FormatFn<'a, T>,
+    style_cell: Option<StyleFn<'a, T, P>>,
+    highlights: HashMap<P, Color>,
+    spacing: usize,
+    rulers: bool,
+    color: bool,
+}
+
+impl<'a, T, P> Renderer<'a, T, P>
+where
+    P: Eq + Hash,
+{
+    pub fn new<F>(format_cell: F) -> Self
+    where
+        F: Fn(&T) -> String + 'a,
+    {
+        Renderer::padded_by(move |item, width| format!("{:width$}", format_cell(item)))
+    }
+
+    // For formatters that want to do their own padding, like `{:width$?}` right aligning numbers
+    fn padded_by<F>(format_cell: F) -> Self
+    where
+        F: Fn(&T, usize) -> String + 'a,
+    {
+        Renderer {
+            format_cell: Box::new(format_cell),
+            style_cell: None,
+            highlights: HashMap::new(),
+            spacing: 0,
+            rulers: false,
+            color: colored::control::SHOULD_COLORIZE.should_colorize(),
+        }
+    }
+
+    // Pads every cell out to at least `spacing` characters
+    pub fn spacing(mut self, spacing: usize) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    // Row numbers down the left and column numbers (one digit per line) along the top. Cells narrower than the
+    // widest one get padded out to match, so each number sits over the start of its column.
+    pub fn rulers(mut self, rulers: bool) -> Self {
+        self.rulers = rulers;
+        self
+    }
+
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    // Picks a text colour for each cell, None leaves it alone
+    pub fn style<F>(mut self, style_cell: F) -> Self
+    where
+        F: Fn(&P, &T) -> Option<Color> + 'a,
+    {
+        self.style_cell = Some(Box::new(style_cell));
+        self
+    }
+
+    // Gives every coordinate in `coords` a `color` background. Later highlights win over earlier ones.
+    pub fn highlight<I>(mut self, coords: I, color: Color) -> Self
+    where
+        I: IntoIterator<Item = P>,
+    {
+        self.highlights
+            .extend(coords.into_iter().map(|coord| (coord, color)));
+        self
+    }
+
+    pub fn write_fmt_to<G, W>(&self, grid: &G, out: &mut W) -> fmt::Result
+    where
+        G: Renderable<Cell = T, Pos = P>,
+        W: fmt::Write,
+    {
+        let (top_left, rows, cols) = grid.extent();
+        let cells = (0..rows)
+            .map(|row| {
+                (0..cols)
+                    .map(|col| {
+                        let coord = grid
+                            .step(&top_left, (row as isize, col as isize))
+                            .expect("Renderable extent runs off the grid");
+                        let item = grid
+                            .cell(&coord)
+                            .expect("Renderable extent runs off the grid");
+                        let text = (self.format_cell)(item, self.spacing);
+                        (coord, item, text)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // The ruler only lines up if every column is the same width, so with rulers on the widest cell sets the
+        // width of all of them. `{:?}` on a char is already 3 wide, for instance.
+        let cell_width = if self.rulers {
+            cells
+                .iter()
+                .flatten()
+                .map(|(_, _, text)| text.chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(1)
+        } else {
+            0
+        };
+
+        let label_width = rows.saturating_sub(1).to_string().len();
+        if self.rulers {
+            self.write_col_ruler(cols, label_width, cell_width, out)?;
+        }
+        for (row, cells) in cells.into_iter().enumerate() {
+            if self.rulers {
+                write!(out, "{:>width$} ", row, width = label_width)?;
+            }
+            for (coord, item, text) in cells {
+                let text = format!("{:cell_width$}", text);
+                if !self.color {
+                    write!(out, "{}", text)?;
+                    continue;
+                }
+                let mut styled = text.normal();
+                if let Some(color) = self
+                    .style_cell
+                    .as_ref()
+                    .and_then(|style| style(&coord, item))
+                {
+                    styled = styled.color(color);
+                }
+                if let Some(color) = self.highlights.get(&coord) {
+                    styled = styled.on_color(*color);
+                }
+                write!(out, "{}", styled)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    // Column numbers written top to bottom, so column 12 shows a 1 above a 2
+    fn write_col_ruler<W: fmt::Write>(
+        &self,
+        cols: usize,
+        label_width: usize,
+        cell_width: usize,
+        out: &mut W,
+    ) -> fmt::Result {
+        let digits = cols.saturating_sub(1).to_string().len();
+        for place in (0..digits).rev() {
+            write!(out, "{:width$} ", "", width = label_width)?;
+            for col in 0..cols {
+                let digit = if place == 0 || col >= 10_usize.pow(place as u32) {
+                    ((col / 10_usize.pow(place as u32)) % 10).to_string()
+                } else {
+                    " ".to_string()
+                };
+                write!(out, "{:width$}", digit, width = cell_width)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_to<G, W>(&self, grid: &G, out: &mut W) -> io::Result<()>
+    where
+        G: Renderable<Cell = T, Pos = P>,
+        W: io::Write,
+    {
+        out.write_all(self.render(grid).as_bytes())
+    }
+
+    pub fn render<G>(&self, grid: &G) -> String
+    where
+        G: Renderable<Cell = T, Pos = P>,
+    {
+        let mut out = String::new();
+        self.write_fmt_to(grid, &mut out).unwrap();
+        out
+    }
+
+    pub fn print<G>(&self, grid: &G)
+    where
+        G: Renderable<Cell = T, Pos = P>,
+    {
+        print!("{}", self.render(grid))
+    }
+}
+
+impl<'a, T, P> Renderer<'a, T, P>
+where
+    T: fmt::Debug,
+    P: Eq + Hash,
+{
+    // Prints each cell with `{:?}`, same as `GridDisplay::print`. Spacing pads with `{:width$?}`, so numbers line up
+    // on the right like they always have.
+    pub fn debug() -> Self {
+        Renderer::padded_by(|item: &T, width| format!("{:width$?}", item))
+    }
+}
@@ -0,0 +1,266 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+
+// Weighted shortest path searches. These don't actually care about grids, they work on any state you can hash.
+// For a grid puzzle the state is usually something like `(Coord, Direction, steps_in_a_row)` and `successors`
+// hands back every state you can move to along with what it costs to get there.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchPath<S> {
+    pub states: Vec<S>,
+    pub cost: u64,
+}
+
+pub fn dijkstra<S, N, I, G>(starts: &[S], successors: N, is_goal: G) -> Option<SearchPath<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, u64)>,
+    G: FnMut(&S) -> bool,
+{
+    astar(starts, successors, is_goal, |_| 0)
+}
+
+// Same as `dijkstra`, but `heuristic` guesses the remaining cost from a state so we can explore fewer states.
+// The guess must never be more than the real cost (Manhattan distance is the usual pick), otherwise the path we
+// hand back might not be the cheapest one.
+pub fn astar<S, N, I, G, H>(
+    starts: &[S],
+    mut successors: N,
+    mut is_goal: G,
+    mut heuristic: H,
+) -> Option<SearchPath<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, u64)>,
+    G: FnMut(&S) -> bool,
+    H: FnMut(&S) -> u64,
+{
+    // States get stored once in `states` and everything else refers to them by index, that way the heap doesn't
+    // need `S: Ord` and we aren't cloning states all over the place
+    let mut states: Vec<S> = Vec::new();
+    let mut index: HashMap<S, usize> = HashMap::new();
+    let mut best: Vec<u64> = Vec::new();
+    let mut prev: Vec<Option<usize>> = Vec::new();
+    let mut heap = BinaryHeap::new();
+
+    for start in starts {
+        if index.contains_key(start) {
+            continue;
+        }
+        index.insert(start.clone(), states.len());
+        heap.push(Reverse((heuristic(start), 0, states.len())));
+        states.push(start.clone());
+        best.push(0);
+        prev.push(None);
+    }
+
+    while let Some(Reverse((_, cost, i_state))) = heap.pop() {
+        if cost > best[i_state] {
+            continue;
+        }
+        if is_goal(&states[i_state]) {
+            let mut path = vec![i_state];
+            while let Some(i_prev) = prev[*path.last().unwrap()] {
+                path.push(i_prev);
+            }
+            return Some(SearchPath {
+                states: path.into_iter().rev().map(|i| states[i].clone()).collect(),
+                cost,
+            });
+        }
+
+        for (next, step_cost) in successors(&states[i_state]) {
+            let next_cost = cost + step_cost;
+            let i_next = match index.get(&next) {
+                Some(&i_next) if best[i_next] <= next_cost => continue,
+                Some(&i_next) => i_next,
+                None => {
+                    index.insert(next.clone(), states.len());
+                    states.push(next);
+                    best.push(u64::MAX);
+                    prev.push(None);
+                    states.len() - 1
+                }
+            };
+            best[i_next] = next_cost;
+            prev[i_next] = Some(i_state);
+            heap.push(Reverse((
+                next_cost + heuristic(&states[i_next]),
+                next_cost,
+                i_next,
+            )));
+        }
+    }
+    None
+}
+
+// Every way of reaching a goal for the lowest possible cost. Rather than listing every path up front (there can
+// be a silly number of them) we keep every cheapest predecessor of each state, then walk those backwards.
+pub struct AllShortestPaths<S> {
+    pub cost: u64,
+    pub starts: HashSet<S>,
+    pub goals: Vec<S>,
+    pub prev: HashMap<S, Vec<S>>,
+}
+
+impl<S> AllShortestPaths<S>
+where
+    S: Clone + Eq + Hash,
+{
+    // Every state that sits on at least one of the shortest paths
+    pub fn states(&self) -> HashSet<S> {
+        let mut seen: HashSet<S> = self.goals.iter().cloned().collect();
+        let mut stack = self.goals.clone();
+        while let Some(state) = stack.pop() {
+            for prev in self.prev.get(&state).into_iter().flatten() {
+                if seen.insert(prev.clone()) {
+                    stack.push(prev.clone());
+                }
+            }
+        }
+        seen
+    }
+
+    // Lists out every shortest path from a start to a goal. Be careful, this can blow up on open grids.
+    // A start can have predecessors of its own when another start reaches it for free, so reaching a start is
+    // the end of one path but we keep going for the others. With zero cost steps the predecessors can loop back
+    // on themselves, so a path never visits the same state twice.
+    pub fn paths(&self) -> Vec<Vec<S>> {
+        let mut paths = Vec::new();
+        for goal in &self.goals {
+            let mut stack = vec![vec![goal.clone()]];
+            while let Some(path) = stack.pop() {
+                let last = path.last().unwrap();
+                if self.starts.contains(last) {
+                    paths.push(path.iter().rev().cloned().collect());
+                }
+                for prev in self.prev.get(last).into_iter().flatten() {
+                    if path.contains(prev) {
+                        continue;
+                    }
+                    let mut longer = path.clone();
+                    longer.push(prev.clone());
+                    stack.push(longer);
+                }
+            }
+        }
+        paths
+    }
+}
+
+pub fn all_shortest_paths<S, N, I, G>(
+    starts: &[S],
+    mut successors: N,
+    mut is_goal: G,
+) -> Option<AllShortestPaths<S>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, u64)>,
+    G: FnMut(&S) -> bool,
+{
+    let mut best: HashMap<S, u64> = HashMap::new();
+    let mut prev: HashMap<S, Vec<S>> = HashMap::new();
+    let mut states: Vec<S> = Vec::new();
+    let mut heap = BinaryHeap::new();
+    let mut goals: Vec<S> = Vec::new();
+    let mut goal_cost = None;
+
+    for start in starts {
+        if best.insert(start.clone(), 0).is_none() {
+            heap.push(Reverse((0, states.len())));
+            states.push(start.clone());
+        }
+    }
+
+    while let Some(Reverse((cost, i_state))) = heap.pop() {
+        // Once we've popped something more expensive than the goal, nothing else can be on a shortest path
+        if goal_cost.is_some_and(|goal_cost| cost > goal_cost) {
+            break;
+        }
+        let state = states[i_state].clone();
+        if cost > best[&state] {
+            continue;
+        }
+        if is_goal(&state) {
+            goal_cost = Some(cost);
+            goals.push(state);
+            continue;
+        }
+
+        for (next, step_cost) in successors(&state) {
+            let next_cost = cost + step_cost;
+            match best.get(&next) {
+                Some(&next_best) if next_best < next_cost => continue,
+                // Starts are in `best` without being in `prev`, so this might be the first predecessor we've seen
+                Some(&next_best) if next_best == next_cost => {
+                    prev.entry(next).or_default().push(state.clone());
+                    continue;
+                }
+                _ => (),
+            }
+            best.insert(next.clone(), next_cost);
+            prev.insert(next.clone(), vec![state.clone()]);
+            heap.push(Reverse((next_cost, states.len())));
+            states.push(next);
+        }
+    }
+
+    Some(AllShortestPaths {
+        cost: goal_cost?,
+        starts: starts.iter().cloned().collect(),
+        goals,
+        prev,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted_paths(found: &AllShortestPaths<i32>) -> Vec<Vec<i32>> {
+        let mut paths = found.paths();
+        paths.sort();
+        paths
+    }
+
+    // Start 1 can be reached for free from start 0, so there are two equally short paths and neither should panic
+    #[test]
+    fn start_reached_again_at_equal_cost() {
+        let found = all_shortest_paths(
+            &[0, 1],
+            |state: &i32| match state {
+                0 => vec![(1, 0)],
+                1 => vec![(2, 1)],
+                _ => vec![],
+            },
+            |state| *state == 2,
+        )
+        .unwrap();
+
+        assert_eq!(found.cost, 1);
+        assert_eq!(sorted_paths(&found), vec![vec![0, 1, 2], vec![1, 2]]);
+    }
+
+    // A free self loop on the start and a free loop between 1 and 3 mustn't send `paths` round forever
+    #[test]
+    fn zero_cost_cycles_end() {
+        let found = all_shortest_paths(
+            &[0],
+            |state: &i32| match state {
+                0 => vec![(0, 0), (1, 1)],
+                1 => vec![(3, 0), (2, 1)],
+                3 => vec![(1, 0), (2, 1)],
+                _ => vec![],
+            },
+            |state| *state == 2,
+        )
+        .unwrap();
+
+        assert_eq!(found.cost, 2);
+        assert_eq!(sorted_paths(&found), vec![vec![0, 1, 2], vec![0, 1, 3, 2]]);
+    }
+}
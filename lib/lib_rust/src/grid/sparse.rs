@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use grid::Grid;
+
+use super::{Connectivity, Coord, Direction, GridLike, Renderable, Renderer};
+
+// Like `Coord`, but it can go negative. Used by `SparseGrid`, where the world can grow in any direction.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct SignedCoord {
+    row: i64,
+    col: i64,
+}
+
+impl SignedCoord {
+    pub fn new(row: i64, col: i64) -> Self {
+        SignedCoord { row, col }
+    }
+
+    pub fn row(&self) -> i64 {
+        self.row
+    }
+
+    pub fn col(&self) -> i64 {
+        self.col
+    }
+
+    pub fn offset(&self, (d_row, d_col): (isize, isize)) -> Option<SignedCoord> {
+        Some(SignedCoord {
+            row: self.row.checked_add(d_row as i64)?,
+            col: self.col.checked_add(d_col as i64)?,
+        })
+    }
+}
+
+impl fmt::Debug for SignedCoord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.col)
+    }
+}
+
+impl From<Coord> for SignedCoord {
+    fn from(coord: Coord) -> Self {
+        SignedCoord::new(coord.row as i64, coord.col as i64)
+    }
+}
+
+// A grid with no fixed size. Only the cells we've set are stored, every other cell is `default`.
+// We keep track of the smallest box holding every stored cell, and that box is what gets printed and what the
+// `GridLike` helpers treat as the world. Rays stop at its edge and BFS never leaves it.
+#[derive(Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<SignedCoord, T>,
+    default: T,
+    bounds: Option<(SignedCoord, SignedCoord)>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(default: T) -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            default,
+            bounds: None,
+        }
+    }
+
+    // Copies every cell of a dense grid in, with the top left of the grid at (0, 0)
+    pub fn from_grid(grid: Grid<T>, default: T) -> Self {
+        let cols = grid.cols();
+        let mut sparse = SparseGrid::new(default);
+        for (i, item) in grid.into_vec().into_iter().enumerate() {
+            sparse.insert(SignedCoord::new((i / cols) as i64, (i % cols) as i64), item);
+        }
+        sparse
+    }
+
+    pub fn get(&self, coord: &SignedCoord) -> &T {
+        self.cells.get(coord).unwrap_or(&self.default)
+    }
+
+    // Gets a cell to change, storing a copy of the default there first if it wasn't set yet
+    pub fn get_mut(&mut self, coord: &SignedCoord) -> &mut T
+    where
+        T: Clone,
+    {
+        if !self.cells.contains_key(coord) {
+            self.insert(*coord, self.default.clone());
+        }
+        self.cells.get_mut(coord).unwrap()
+    }
+
+    pub fn insert(&mut self, coord: SignedCoord, value: T) -> Option<T> {
+        self.grow_bounds(coord);
+        self.cells.insert(coord, value)
+    }
+
+    // Puts a cell back to the default. If it was on the edge of the bounding box, the box might shrink.
+    pub fn remove(&mut self, coord: &SignedCoord) -> Option<T> {
+        let removed = self.cells.remove(coord)?;
+        if let Some((min, max)) = self.bounds {
+            if [min.row, max.row].contains(&coord.row) || [min.col, max.col].contains(&coord.col) {
+                self.recompute_bounds();
+            }
+        }
+        Some(removed)
+    }
+
+    fn grow_bounds(&mut self, coord: SignedCoord) {
+        self.bounds = Some(match self.bounds {
+            None => (coord, coord),
+            Some((min, max)) => (
+                SignedCoord::new(min.row.min(coord.row), min.col.min(coord.col)),
+                SignedCoord::new(max.row.max(coord.row), max.col.max(coord.col)),
+            ),
+        });
+    }
+
+    fn recompute_bounds(&mut self) {
+        self.bounds = None;
+        let coords = self.cells.keys().copied().collect::<Vec<_>>();
+        for coord in coords {
+            self.grow_bounds(coord);
+        }
+    }
+
+    // The top left and bottom right corners of the box holding every stored cell, or None if nothing is stored
+    pub fn bounds(&self) -> Option<(SignedCoord, SignedCoord)> {
+        self.bounds
+    }
+
+    pub fn contains(&self, coord: &SignedCoord) -> bool {
+        self.bounds.is_some_and(|(min, max)| {
+            (min.row..=max.row).contains(&coord.row) && (min.col..=max.col).contains(&coord.col)
+        })
+    }
+
+    pub fn default_value(&self) -> &T {
+        &self.default
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // Only the cells that have been set, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (SignedCoord, &T)> {
+        self.cells.iter().map(|(coord, item)| (*coord, item))
+    }
+
+    // Copies the bounding box out into a dense grid. Cell (0, 0) of the grid is the top left corner of the box.
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let Some((min, max)) = self.bounds else {
+            return Grid::from_vec(vec![], 0);
+        };
+        let cols = (max.col - min.col + 1) as usize;
+        let cells = (min.row..=max.row)
+            .flat_map(|row| (min.col..=max.col).map(move |col| SignedCoord::new(row, col)))
+            .map(|coord| self.get(&coord).clone())
+            .collect();
+        Grid::from_vec(cells, cols)
+    }
+
+    pub fn print(&self)
+    where
+        T: fmt::Debug,
+    {
+        Renderer::debug().print(self);
+        println!()
+    }
+}
+
+impl<T> GridLike for SparseGrid<T> {
+    type Cell = T;
+    type Pos = SignedCoord;
+    type Map<V> = HashMap<SignedCoord, V>;
+    type Offset = (isize, isize);
+    type Direction = Direction;
+    type Connectivity = Connectivity;
+
+    fn cell(&self, pos: &SignedCoord) -> Option<&T> {
+        if self.contains(pos) {
+            Some(self.get(pos))
+        } else {
+            None
+        }
+    }
+
+    fn step(&self, pos: &SignedCoord, offset: (isize, isize)) -> Option<SignedCoord> {
+        pos.offset(offset)
+    }
+
+    fn new_map<V>(&self) -> HashMap<SignedCoord, V> {
+        HashMap::new()
+    }
+}
+
+// Draws the bounding box, same as `print`
+impl<T> Renderable for SparseGrid<T> {
+    fn extent(&self) -> (SignedCoord, usize, usize) {
+        match self.bounds {
+            Some((min, max)) => (
+                min,
+                (max.row - min.row + 1) as usize,
+                (max.col - min.col + 1) as usize,
+            ),
+            None => (SignedCoord::new(0, 0), 0, 0),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use grid::Grid;
+
+use super::{Connectivity, Coord, Direction, GridExt, GridLike, Renderable, SignedCoord};
+
+// A grid where walking off one edge brings you back on the opposite edge, like a donut. Positions are always
+// plain `Coord`s on the base grid, it's only stepping that wraps around. Careful with `ray`, it will go around
+// and around forever unless you `take` or `until` it.
+pub struct WrappingView<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+impl<'a, T> WrappingView<'a, T> {
+    pub(crate) fn new(grid: &'a Grid<T>) -> Self {
+        WrappingView { grid }
+    }
+
+    // Where any signed coordinate ends up once it's been wrapped onto the grid
+    pub fn wrap(&self, coord: &SignedCoord) -> Option<Coord> {
+        if self.grid.is_empty() {
+            return None;
+        }
+        Some(Coord::new(
+            coord.row().rem_euclid(self.grid.rows() as i64) as usize,
+            coord.col().rem_euclid(self.grid.cols() as i64) as usize,
+        ))
+    }
+}
+
+impl<T> GridLike for WrappingView<'_, T> {
+    type Cell = T;
+    type Pos = Coord;
+    type Map<V> = Grid<Option<V>>;
+    type Offset = (isize, isize);
+    type Direction = Direction;
+    type Connectivity = Connectivity;
+
+    fn cell(&self, pos: &Coord) -> Option<&T> {
+        self.grid.get_at_coord(pos)
+    }
+
+    fn step(&self, pos: &Coord, offset: (isize, isize)) -> Option<Coord> {
+        self.wrap(&SignedCoord::from(*pos).offset(offset)?)
+    }
+
+    fn new_map<V>(&self) -> Grid<Option<V>> {
+        Grid::new(self.grid.rows(), self.grid.cols())
+    }
+}
+
+// The grid repeated forever in every direction. Tile (0, 0) is the base grid itself, tile (0, 1) is the copy to
+// its right, tile (-1, 0) the copy above it and so on.
+// Out of the box this never ends, so a BFS or flood fill over it would never finish either. Use `within` to only
+// keep the tiles near the middle.
+pub struct TiledView<'a, T> {
+    grid: &'a Grid<T>,
+    radius: Option<u32>,
+}
+
+impl<'a, T> TiledView<'a, T> {
+    pub(crate) fn new(grid: &'a Grid<T>) -> Self {
+        TiledView { grid, radius: None }
+    }
+
+    // Only keep tiles that are at most `radius` tiles away from tile (0, 0) in each direction,
+    // so `within(2)` leaves a 5x5 block of tiles
+    pub fn within(self, radius: u32) -> Self {
+        TiledView {
+            radius: Some(radius),
+            ..self
+        }
+    }
+
+    // Which copy of the grid a coordinate lands in, as (tile row, tile col). An empty grid has no tiles at all.
+    pub fn tile_of(&self, coord: &SignedCoord) -> Option<(i64, i64)> {
+        if self.grid.is_empty() {
+            return None;
+        }
+        Some((
+            coord.row().div_euclid(self.grid.rows() as i64),
+            coord.col().div_euclid(self.grid.cols() as i64),
+        ))
+    }
+
+    // The cell on the base grid that a coordinate is a copy of, which is the same thing wrapping does
+    pub fn base_coord(&self, coord: &SignedCoord) -> Option<Coord> {
+        WrappingView::new(self.grid).wrap(coord)
+    }
+}
+
+impl<T> GridLike for TiledView<'_, T> {
+    type Cell = T;
+    type Pos = SignedCoord;
+    type Map<V> = HashMap<SignedCoord, V>;
+    type Offset = (isize, isize);
+    type Direction = Direction;
+    type Connectivity = Connectivity;
+
+    fn cell(&self, pos: &SignedCoord) -> Option<&T> {
+        if let Some(radius) = self.radius {
+            let (tile_row, tile_col) = self.tile_of(pos)?;
+            if tile_row.unsigned_abs() > radius as u64 || tile_col.unsigned_abs() > radius as u64 {
+                return None;
+            }
+        }
+        self.grid.get_at_coord(&self.base_coord(pos)?)
+    }
+
+    fn step(&self, pos: &SignedCoord, offset: (isize, isize)) -> Option<SignedCoord> {
+        pos.offset(offset)
+    }
+
+    fn new_map<V>(&self) -> HashMap<SignedCoord, V> {
+        HashMap::new()
+    }
+}
+
+// Just the base grid, since every step wraps back onto it
+impl<T> Renderable for WrappingView<'_, T> {
+    fn extent(&self) -> (Coord, usize, usize) {
+        (Coord::new(0, 0), self.grid.rows(), self.grid.cols())
+    }
+}
+
+// Every tile kept by `within`, or just tile (0, 0) if there's no limit
+impl<T> Renderable for TiledView<'_, T> {
+    fn extent(&self) -> (SignedCoord, usize, usize) {
+        let (rows, cols) = self.grid.size();
+        let radius = self.radius.unwrap_or(0) as usize;
+        let tiles = 2 * radius + 1;
+        (
+            SignedCoord::new(-((radius * rows) as i64), -((radius * cols) as i64)),
+            tiles * rows,
+            tiles * cols,
+        )
+    }
+}
@@ -0,0 +1,66 @@
+use grid::Grid;
+
+use super::{Coord, Direction};
+
+// Slides every mobile cell as far towards `dir` as it will go. A mobile cell stops when it hits the wall, an
+// obstacle or another mobile cell that has already stopped. Anything that's neither mobile nor an obstacle is
+// empty space and gets swapped backwards as mobile cells slide past it.
+//
+// Each line (a column when tilting North/South, a row for East/West) is done in one pass. We walk it starting at
+// the wall we're tilting towards and keep `free`, the spot the next mobile cell would land on. Obstacles move
+// `free` to just past themselves, mobile cells swap into `free` and push it along by one.
+//
+// Hands back every cell that moved as (where it was, where it ended up).
+pub(crate) fn tilt<T, M, O>(
+    grid: &mut Grid<T>,
+    dir: &Direction,
+    mut is_mobile: M,
+    mut is_obstacle: O,
+) -> Vec<(Coord, Coord)>
+where
+    M: FnMut(&T) -> bool,
+    O: FnMut(&T) -> bool,
+{
+    let (rows, cols) = grid.size();
+    let mut moves = Vec::new();
+
+    let num_lines = match dir {
+        Direction::North | Direction::South => cols,
+        Direction::East | Direction::West => rows,
+    };
+    for i_line in 0..num_lines {
+        let mut line: Vec<&mut T> = match dir {
+            Direction::North | Direction::South => grid.iter_col_mut(i_line).collect(),
+            Direction::East | Direction::West => grid.iter_row_mut(i_line).collect(),
+        };
+        // Make index 0 the wall that everything is sliding towards
+        let reversed = matches!(dir, Direction::South | Direction::East);
+        if reversed {
+            line.reverse();
+        }
+        let len = line.len();
+        let to_coord = |i: usize| {
+            let i = if reversed { len - 1 - i } else { i };
+            match dir {
+                Direction::North | Direction::South => Coord::new(i, i_line),
+                Direction::East | Direction::West => Coord::new(i_line, i),
+            }
+        };
+
+        let mut free = 0;
+        for i in 0..line.len() {
+            if is_obstacle(line[i]) {
+                free = i + 1;
+            } else if is_mobile(line[i]) {
+                if free != i {
+                    let (before, after) = line.split_at_mut(i);
+                    std::mem::swap(before[free], after[0]);
+                    moves.push((to_coord(i), to_coord(free)));
+                }
+                free += 1;
+            }
+        }
+    }
+
+    moves
+}
@@ -0,0 +1,134 @@
+use core::fmt;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use grid::Grid;
+
+use super::{Connectivity, Coord, GridExt, GridLike, Walk};
+
+// Which way to read the grid when looking for tokens. `Diagonals` run down and to the right, `AntiDiagonals`
+// run down and to the left.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Lines {
+    Rows,
+    Columns,
+    Diagonals,
+    AntiDiagonals,
+}
+
+impl Lines {
+    pub(crate) fn step(&self) -> (isize, isize) {
+        match self {
+            Lines::Rows => (0, 1),
+            Lines::Columns => (1, 0),
+            Lines::Diagonals => (1, 1),
+            Lines::AntiDiagonals => (1, -1),
+        }
+    }
+
+    // Where each line starts. A line never wraps onto the next one, so a number at the end of one row and
+    // another at the start of the next stay separate.
+    pub(crate) fn starts(&self, rows: usize, cols: usize) -> Vec<Coord> {
+        let left_edge = (0..rows).map(|row| Coord::new(row, 0));
+        let top_edge = (0..cols).map(|col| Coord::new(0, col));
+        match self {
+            Lines::Rows => left_edge.collect(),
+            Lines::Columns => top_edge.collect(),
+            Lines::Diagonals => left_edge.chain(top_edge.skip(1)).collect(),
+            Lines::AntiDiagonals => top_edge
+                .chain((1..rows).map(|row| Coord::new(row, cols - 1)))
+                .collect(),
+        }
+    }
+}
+
+// A run of matching cells, in the order they were read, along with what they spell out parsed into a `V`
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub struct Token<V> {
+    pub value: V,
+    pub cells: Vec<Coord>,
+}
+
+impl<V> Token<V> {
+    pub fn start(&self) -> Coord {
+        self.cells[0]
+    }
+
+    pub fn end(&self) -> Coord {
+        *self.cells.last().unwrap()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // Every cell touching the token, diagonals included, that isn't part of the token itself. Each cell only
+    // shows up once and they come back in reading order.
+    pub fn adjacent<'a, T>(&self, grid: &'a Grid<T>) -> Vec<(Coord, &'a T)> {
+        let own = self.cells.iter().collect::<HashSet<_>>();
+        let mut adjacent = self
+            .cells
+            .iter()
+            .flat_map(|coord| grid.neighbours(coord, Connectivity::Eight))
+            .filter(|coord| !own.contains(coord))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        adjacent.sort_by_key(|coord| (coord.row, coord.col));
+        adjacent
+            .into_iter()
+            .map(|coord| (coord, grid.get_at_coord(&coord).unwrap()))
+            .collect()
+    }
+}
+
+pub(crate) fn tokens<T, V, P>(grid: &Grid<T>, lines: Lines, mut is_token: P) -> Vec<Token<V>>
+where
+    T: fmt::Display,
+    V: FromStr,
+    P: FnMut(&T) -> bool,
+{
+    let (rows, cols) = grid.size();
+    if rows == 0 || cols == 0 {
+        return Vec::new();
+    }
+    let mut tokens = Vec::new();
+    for start in lines.starts(rows, cols) {
+        let mut run: Vec<(Coord, &T)> = Vec::new();
+        // The extra None on the end makes sure a run that reaches the edge of the grid still gets finished off
+        let walk = Walk::new(grid, Some(start), lines.step(), None).map(Some);
+        for cell in walk.chain([None]) {
+            match cell {
+                Some((coord, item)) if is_token(item) => run.push((coord, item)),
+                _ if !run.is_empty() => {
+                    tokens.push(finish(&run));
+                    run.clear();
+                }
+                _ => (),
+            }
+        }
+    }
+    tokens
+}
+
+fn finish<T, V>(run: &[(Coord, &T)]) -> Token<V>
+where
+    T: fmt::Display,
+    V: FromStr,
+{
+    let text = run
+        .iter()
+        .map(|(_, item)| item.to_string())
+        .collect::<String>();
+    let value = text
+        .parse()
+        .unwrap_or_else(|_| panic!("Couldn't parse token {:?} at {:?}", text, run[0].0));
+    Token {
+        value,
+        cells: run.iter().map(|(coord, _)| *coord).collect(),
+    }
+}
@@ -0,0 +1,297 @@
+use std::ops::{Index, IndexMut};
+
+use grid::Grid;
+
+use super::{Connectivity, Coord, Direction, GridExt, GridLike, Renderable};
+
+// The 8 ways of turning and flipping a grid that still leave a grid. The names follow the methods on `Grid` that
+// do the same thing in place, e.g. `FlipCols` is what `grid.flip_cols()` does.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Transform {
+    Identity,
+    RotateRight,
+    RotateHalf,
+    RotateLeft,
+    FlipCols,
+    FlipRows,
+    Transpose,
+    AntiTranspose,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::RotateRight,
+        Transform::RotateHalf,
+        Transform::RotateLeft,
+        Transform::FlipCols,
+        Transform::FlipRows,
+        Transform::Transpose,
+        Transform::AntiTranspose,
+    ];
+
+    // Every transform boils down to maybe swapping rows with columns, then maybe counting rows from the bottom,
+    // then maybe counting columns from the right. This gives back (swap, flip rows, flip cols).
+    fn parts(&self) -> (bool, bool, bool) {
+        match self {
+            Transform::Identity => (false, false, false),
+            Transform::RotateRight => (true, true, false),
+            Transform::RotateHalf => (false, true, true),
+            Transform::RotateLeft => (true, false, true),
+            Transform::FlipCols => (false, false, true),
+            Transform::FlipRows => (false, true, false),
+            Transform::Transpose => (true, false, false),
+            Transform::AntiTranspose => (true, true, true),
+        }
+    }
+
+    // The transform that undoes this one
+    pub fn inverse(&self) -> Transform {
+        match self {
+            Transform::RotateRight => Transform::RotateLeft,
+            Transform::RotateLeft => Transform::RotateRight,
+            other => *other,
+        }
+    }
+
+    // The size of a grid of size (rows, cols) once it has been transformed
+    pub fn size(&self, (rows, cols): (usize, usize)) -> (usize, usize) {
+        if self.parts().0 {
+            (cols, rows)
+        } else {
+            (rows, cols)
+        }
+    }
+
+    // Takes a coordinate in the transformed grid and finds the coordinate it came from in a base grid of
+    // `base_size`. The coordinate isn't bounds checked.
+    pub fn to_base(&self, coord: &Coord, (rows, cols): (usize, usize)) -> Coord {
+        let (swap, flip_rows, flip_cols) = self.parts();
+        let (row, col) = if swap {
+            (coord.col, coord.row)
+        } else {
+            (coord.row, coord.col)
+        };
+        Coord::new(
+            if flip_rows { rows - 1 - row } else { row },
+            if flip_cols { cols - 1 - col } else { col },
+        )
+    }
+
+    // The other way round, from a coordinate in a base grid of `base_size` to where it shows up once transformed
+    pub fn from_base(&self, coord: &Coord, (rows, cols): (usize, usize)) -> Coord {
+        let (swap, flip_rows, flip_cols) = self.parts();
+        let row = if flip_rows {
+            rows - 1 - coord.row
+        } else {
+            coord.row
+        };
+        let col = if flip_cols {
+            cols - 1 - coord.col
+        } else {
+            coord.col
+        };
+        if swap {
+            Coord::new(col, row)
+        } else {
+            Coord::new(row, col)
+        }
+    }
+}
+
+// A rotated or flipped look at a grid. Nothing is copied, every read works out which base cell it means.
+pub struct GridView<'a, T> {
+    grid: &'a Grid<T>,
+    transform: Transform,
+}
+
+impl<'a, T> GridView<'a, T> {
+    pub(crate) fn new(grid: &'a Grid<T>, transform: Transform) -> Self {
+        GridView { grid, transform }
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.transform.size(self.grid.size())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.size().0
+    }
+
+    pub fn cols(&self) -> usize {
+        self.size().1
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&'a T> {
+        self.get_at_coord(&Coord::new(row, col))
+    }
+
+    pub fn get_at_coord(&self, coord: &Coord) -> Option<&'a T> {
+        self.grid.get_at_coord(&self.to_base(coord)?)
+    }
+
+    // Where a coordinate in the view lives on the base grid, or None if it's off the view
+    pub fn to_base(&self, coord: &Coord) -> Option<Coord> {
+        let (rows, cols) = self.size();
+        if coord.row >= rows || coord.col >= cols {
+            return None;
+        }
+        Some(self.transform.to_base(coord, self.grid.size()))
+    }
+
+    // Where a base grid coordinate shows up in the view, or None if it's off the base grid
+    pub fn from_base(&self, coord: &Coord) -> Option<Coord> {
+        self.grid.get_at_coord(coord)?;
+        Some(self.transform.from_base(coord, self.grid.size()))
+    }
+
+    pub fn iter_row(&self, row: usize) -> impl Iterator<Item = &'a T> + '_ {
+        (0..self.cols()).map_while(move |col| self.get(row, col))
+    }
+
+    // Copies the view out into a brand new grid
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = (0..self.rows())
+            .flat_map(|row| self.iter_row(row).cloned().collect::<Vec<_>>())
+            .collect();
+        Grid::from_vec(cells, self.cols())
+    }
+}
+
+impl<T> Index<(usize, usize)> for GridView<'_, T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        self.get(row, col).expect("Index is outside of the view")
+    }
+}
+
+impl<T> GridLike for GridView<'_, T> {
+    type Cell = T;
+    type Pos = Coord;
+    type Map<V> = Grid<Option<V>>;
+    type Offset = (isize, isize);
+    type Direction = Direction;
+    type Connectivity = Connectivity;
+
+    fn cell(&self, pos: &Coord) -> Option<&T> {
+        self.get_at_coord(pos)
+    }
+
+    fn step(&self, pos: &Coord, offset: (isize, isize)) -> Option<Coord> {
+        pos.offset(offset)
+    }
+
+    fn new_map<V>(&self) -> Grid<Option<V>> {
+        Grid::new(self.rows(), self.cols())
+    }
+}
+
+impl<T> Renderable for GridView<'_, T> {
+    fn extent(&self) -> (Coord, usize, usize) {
+        (Coord::new(0, 0), self.rows(), self.cols())
+    }
+}
+
+// Same as `GridView`, but writes go straight through to the base grid
+pub struct GridViewMut<'a, T> {
+    grid: &'a mut Grid<T>,
+    transform: Transform,
+}
+
+impl<'a, T> GridViewMut<'a, T> {
+    pub(crate) fn new(grid: &'a mut Grid<T>, transform: Transform) -> Self {
+        GridViewMut { grid, transform }
+    }
+
+    // A read only view of the same thing, handy for handing to the `GridLike` helpers
+    pub fn as_view(&self) -> GridView<'_, T> {
+        GridView::new(self.grid, self.transform)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.transform.size(self.grid.size())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.size().0
+    }
+
+    pub fn cols(&self) -> usize {
+        self.size().1
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<&T> {
+        self.get_at_coord(&Coord::new(row, col))
+    }
+
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Option<&mut T> {
+        self.get_at_coord_mut(&Coord::new(row, col))
+    }
+
+    pub fn get_at_coord(&self, coord: &Coord) -> Option<&T> {
+        self.grid.get_at_coord(&self.to_base(coord)?)
+    }
+
+    pub fn get_at_coord_mut(&mut self, coord: &Coord) -> Option<&mut T> {
+        let base = self.to_base(coord)?;
+        self.grid.get_at_coord_mut(&base)
+    }
+
+    pub fn to_base(&self, coord: &Coord) -> Option<Coord> {
+        self.as_view().to_base(coord)
+    }
+
+    pub fn from_base(&self, coord: &Coord) -> Option<Coord> {
+        self.as_view().from_base(coord)
+    }
+}
+
+impl<T> Index<(usize, usize)> for GridViewMut<'_, T> {
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        self.get(row, col).expect("Index is outside of the view")
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for GridViewMut<'_, T> {
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        self.get_mut(row, col)
+            .expect("Index is outside of the view")
+    }
+}
+
+impl<T> GridLike for GridViewMut<'_, T> {
+    type Cell = T;
+    type Pos = Coord;
+    type Map<V> = Grid<Option<V>>;
+    type Offset = (isize, isize);
+    type Direction = Direction;
+    type Connectivity = Connectivity;
+
+    fn cell(&self, pos: &Coord) -> Option<&T> {
+        self.get_at_coord(pos)
+    }
+
+    fn step(&self, pos: &Coord, offset: (isize, isize)) -> Option<Coord> {
+        pos.offset(offset)
+    }
+
+    fn new_map<V>(&self) -> Grid<Option<V>> {
+        Grid::new(self.rows(), self.cols())
+    }
+}
+
+impl<T> Renderable for GridViewMut<'_, T> {
+    fn extent(&self) -> (Coord, usize, usize) {
+        (Coord::new(0, 0), self.rows(), self.cols())
+    }
+}
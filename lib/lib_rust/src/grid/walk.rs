@@ -0,0 +1,82 @@
+use super::GridLike;
+
+// Walks across a grid one step at a time, yielding each cell's position along with a reference to it.
+// The walk ends as soon as it steps out of the grid (or after `remaining` steps if there is a limit).
+// `ray`, `line`, `row_iter` and `col_iter` all hand one of these back.
+pub struct Walk<'a, G: GridLike> {
+    grid: &'a G,
+    next: Option<G::Pos>,
+    step: G::Offset,
+    remaining: Option<usize>,
+}
+
+impl<'a, G: GridLike> Walk<'a, G> {
+    pub(crate) fn new(
+        grid: &'a G,
+        start: Option<G::Pos>,
+        step: G::Offset,
+        remaining: Option<usize>,
+    ) -> Self {
+        Walk {
+            grid,
+            next: start,
+            step,
+            remaining,
+        }
+    }
+
+    // Keep walking until we hit a cell that matches `stop`. Unlike `take_while`, the cell that stopped us is
+    // still yielded. That's usually what we want for line of sight: the tree that blocks the view is still seen.
+    pub fn until<P>(self, stop: P) -> Until<'a, G, P>
+    where
+        P: FnMut(&G::Cell) -> bool,
+    {
+        Until {
+            walk: self,
+            stop,
+            done: false,
+        }
+    }
+}
+
+impl<'a, G: GridLike> Iterator for Walk<'a, G> {
+    type Item = (G::Pos, &'a G::Cell);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == Some(0) {
+            return None;
+        }
+        let pos = self.next?;
+        let Some(item) = self.grid.cell(&pos) else {
+            self.next = None;
+            return None;
+        };
+        self.next = self.grid.step(&pos, self.step);
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= 1;
+        }
+        Some((pos, item))
+    }
+}
+
+pub struct Until<'a, G: GridLike, P> {
+    walk: Walk<'a, G>,
+    stop: P,
+    done: bool,
+}
+
+impl<'a, G: GridLike, P> Iterator for Until<'a, G, P>
+where
+    P: FnMut(&G::Cell) -> bool,
+{
+    type Item = (G::Pos, &'a G::Cell);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let (pos, item) = self.walk.next()?;
+        self.done = (self.stop)(item);
+        Some((pos, item))
+    }
+}
@@ -0,0 +1,378 @@
+use core::fmt;
+use std::collections::HashMap;
+
+use crate::grid::{GridLike, Heading, Neighbourhood, PosMap, Walk};
+
+// Three dimensional versions of the grid helpers, for falling bricks, lava droplets and the like. This tries to
+// mirror `grid` as closely as it can so a solution can go from 2-D to 3-D without learning a new API. The big
+// difference is that `Coord3` is always signed, since 3-D puzzles love negative coordinates. Both grids are
+// `GridLike`, so the searches are the exact same code as in 2-D and come back as a plain `DistanceMap`.
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct Coord3 {
+    x: i64,
+    y: i64,
+    z: i64,
+}
+
+impl Coord3 {
+    pub fn new(x: i64, y: i64, z: i64) -> Self {
+        Coord3 { x, y, z }
+    }
+
+    pub fn x(&self) -> i64 {
+        self.x
+    }
+
+    pub fn y(&self) -> i64 {
+        self.y
+    }
+
+    pub fn z(&self) -> i64 {
+        self.z
+    }
+
+    pub fn offset(&self, (d_x, d_y, d_z): (i64, i64, i64)) -> Option<Coord3> {
+        Some(Coord3 {
+            x: self.x.checked_add(d_x)?,
+            y: self.y.checked_add(d_y)?,
+            z: self.z.checked_add(d_z)?,
+        })
+    }
+
+    pub fn manhattan(&self, other: &Coord3) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y) + self.z.abs_diff(other.z)
+    }
+}
+
+impl fmt::Debug for Coord3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.x, self.y, self.z)
+    }
+}
+
+// 3-D puzzles don't agree on which way is up, so these are named after the axis they move along
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Direction3 {
+    MinusX,
+    PlusX,
+    MinusY,
+    PlusY,
+    MinusZ,
+    PlusZ,
+}
+
+impl Direction3 {
+    pub const ALL: [Direction3; 6] = [
+        Direction3::MinusX,
+        Direction3::PlusX,
+        Direction3::MinusY,
+        Direction3::PlusY,
+        Direction3::MinusZ,
+        Direction3::PlusZ,
+    ];
+
+    pub fn offset(&self) -> (i64, i64, i64) {
+        match self {
+            Direction3::MinusX => (-1, 0, 0),
+            Direction3::PlusX => (1, 0, 0),
+            Direction3::MinusY => (0, -1, 0),
+            Direction3::PlusY => (0, 1, 0),
+            Direction3::MinusZ => (0, 0, -1),
+            Direction3::PlusZ => (0, 0, 1),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction3 {
+        match self {
+            Direction3::MinusX => Direction3::PlusX,
+            Direction3::PlusX => Direction3::MinusX,
+            Direction3::MinusY => Direction3::PlusY,
+            Direction3::PlusY => Direction3::MinusY,
+            Direction3::MinusZ => Direction3::PlusZ,
+            Direction3::PlusZ => Direction3::MinusZ,
+        }
+    }
+}
+
+impl Heading for Direction3 {
+    type Offset = (i64, i64, i64);
+    const ALL: &'static [Direction3] = &Direction3::ALL;
+
+    fn offset(&self) -> (i64, i64, i64) {
+        Direction3::offset(self)
+    }
+}
+
+// `Six` is only the faces of a cube, `TwentySix` adds the edges and corners
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Connectivity3 {
+    Six,
+    TwentySix,
+}
+
+// Every offset in the 3x3x3 cube around a cell apart from the cell itself
+const TWENTY_SIX: [(i64, i64, i64); 26] = {
+    let mut offsets = [(0, 0, 0); 26];
+    let mut i = 0;
+    let mut n = 0;
+    while n < 27 {
+        let (x, y, z) = (n / 9 - 1, n / 3 % 3 - 1, n % 3 - 1);
+        if x != 0 || y != 0 || z != 0 {
+            offsets[i] = (x, y, z);
+            i += 1;
+        }
+        n += 1;
+    }
+    offsets
+};
+
+impl Connectivity3 {
+    pub fn offsets(&self) -> &'static [(i64, i64, i64)] {
+        match self {
+            Connectivity3::Six => &[
+                (-1, 0, 0),
+                (1, 0, 0),
+                (0, -1, 0),
+                (0, 1, 0),
+                (0, 0, -1),
+                (0, 0, 1),
+            ],
+            Connectivity3::TwentySix => &TWENTY_SIX,
+        }
+    }
+}
+
+impl Neighbourhood for Connectivity3 {
+    type Offset = (i64, i64, i64);
+
+    fn offsets(&self) -> &'static [(i64, i64, i64)] {
+        Connectivity3::offsets(self)
+    }
+}
+
+// The step and number of cells for a straight line from `from` to `to`, both ends included. Straight means along
+// an axis or a diagonal, so every axis that changes has to change by the same amount.
+fn line_step(from: &Coord3, to: &Coord3) -> Option<((i64, i64, i64), usize)> {
+    let deltas = [to.x - from.x, to.y - from.y, to.z - from.z];
+    let len = deltas.iter().map(|d| d.unsigned_abs()).max().unwrap();
+    if deltas.iter().any(|d| *d != 0 && d.unsigned_abs() != len) {
+        return None;
+    }
+    let [d_x, d_y, d_z] = deltas.map(i64::signum);
+    Some(((d_x, d_y, d_z), len as usize + 1))
+}
+
+// A fixed size block of cells. Like `Grid`, anything outside of it is simply not there.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Grid3<T> {
+    size: (usize, usize, usize),
+    cells: Vec<T>,
+}
+
+impl<T> Grid3<T> {
+    pub fn new(size_x: usize, size_y: usize, size_z: usize) -> Self
+    where
+        T: Default + Clone,
+    {
+        Grid3::init(size_x, size_y, size_z, T::default())
+    }
+
+    pub fn init(size_x: usize, size_y: usize, size_z: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid3 {
+            size: (size_x, size_y, size_z),
+            cells: vec![value; size_x * size_y * size_z],
+        }
+    }
+
+    pub fn size(&self) -> (usize, usize, usize) {
+        self.size
+    }
+
+    fn index(&self, coord: &Coord3) -> Option<usize> {
+        let (size_x, size_y, size_z) = self.size;
+        let x = usize::try_from(coord.x).ok().filter(|x| *x < size_x)?;
+        let y = usize::try_from(coord.y).ok().filter(|y| *y < size_y)?;
+        let z = usize::try_from(coord.z).ok().filter(|z| *z < size_z)?;
+        Some((z * size_y + y) * size_x + x)
+    }
+
+    pub fn get(&self, coord: &Coord3) -> Option<&T> {
+        self.cells.get(self.index(coord)?)
+    }
+
+    pub fn get_mut(&mut self, coord: &Coord3) -> Option<&mut T> {
+        let i = self.index(coord)?;
+        self.cells.get_mut(i)
+    }
+
+    // Every cell from `from` to `to` inclusive, if they're in a straight line (along an axis or a diagonal)
+    pub fn line(&self, from: &Coord3, to: &Coord3) -> Option<Walk<'_, Grid3<T>>> {
+        let (step, len) = line_step(from, to)?;
+        Some(Walk::new(self, Some(*from), step, Some(len)))
+    }
+
+    pub fn indexed_iter(&self) -> impl Iterator<Item = (Coord3, &T)> {
+        let (size_x, size_y, _) = self.size;
+        self.cells.iter().enumerate().map(move |(i, item)| {
+            let coord = Coord3::new(
+                (i % size_x) as i64,
+                ((i / size_x) % size_y) as i64,
+                (i / (size_x * size_y)) as i64,
+            );
+            (coord, item)
+        })
+    }
+}
+
+// An unbounded 3-D grid where only the cells we set are stored and everything else is `default`. As with
+// `SparseGrid`, the box around every stored cell is what the search helpers treat as the world.
+#[derive(Clone, Debug)]
+pub struct SparseGrid3<T> {
+    cells: HashMap<Coord3, T>,
+    default: T,
+    bounds: Option<(Coord3, Coord3)>,
+}
+
+impl<T> SparseGrid3<T> {
+    pub fn new(default: T) -> Self {
+        SparseGrid3 {
+            cells: HashMap::new(),
+            default,
+            bounds: None,
+        }
+    }
+
+    pub fn get(&self, coord: &Coord3) -> &T {
+        self.cells.get(coord).unwrap_or(&self.default)
+    }
+
+    pub fn insert(&mut self, coord: Coord3, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            None => (coord, coord),
+            Some((min, max)) => (
+                Coord3::new(min.x.min(coord.x), min.y.min(coord.y), min.z.min(coord.z)),
+                Coord3::new(max.x.max(coord.x), max.y.max(coord.y), max.z.max(coord.z)),
+            ),
+        });
+        self.cells.insert(coord, value)
+    }
+
+    // Unlike `SparseGrid::remove` this leaves the bounds alone, so the world never shrinks
+    pub fn remove(&mut self, coord: &Coord3) -> Option<T> {
+        self.cells.remove(coord)
+    }
+
+    pub fn bounds(&self) -> Option<(Coord3, Coord3)> {
+        self.bounds
+    }
+
+    // Pushes the bounds out by `margin` in every direction. Useful for flood filling around the outside of a
+    // shape, where you need a layer of empty space all the way round.
+    pub fn grow_bounds(&mut self, margin: i64) {
+        if let Some((min, max)) = self.bounds {
+            self.bounds = Some((
+                Coord3::new(min.x - margin, min.y - margin, min.z - margin),
+                Coord3::new(max.x + margin, max.y + margin, max.z + margin),
+            ));
+        }
+    }
+
+    pub fn contains(&self, coord: &Coord3) -> bool {
+        self.bounds.is_some_and(|(min, max)| {
+            (min.x..=max.x).contains(&coord.x)
+                && (min.y..=max.y).contains(&coord.y)
+                && (min.z..=max.z).contains(&coord.z)
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Coord3, &T)> {
+        self.cells.iter().map(|(coord, item)| (*coord, item))
+    }
+
+    pub fn line(&self, from: &Coord3, to: &Coord3) -> Option<Walk<'_, SparseGrid3<T>>> {
+        let (step, len) = line_step(from, to)?;
+        Some(Walk::new(self, Some(*from), step, Some(len)))
+    }
+}
+
+impl<V> PosMap<Coord3, V> for Grid3<Option<V>> {
+    fn get(&self, pos: &Coord3) -> Option<&V> {
+        Grid3::get(self, pos)?.as_ref()
+    }
+
+    // Panics if `pos` is outside the grid, the same way the 2-D one does
+    fn insert(&mut self, pos: Coord3, value: V) {
+        *self.get_mut(&pos).expect("Coordinate is outside the grid") = Some(value);
+    }
+
+    fn entries(&self) -> Box<dyn Iterator<Item = (Coord3, &V)> + '_> {
+        Box::new(
+            self.indexed_iter()
+                .filter_map(|(coord, value)| Some((coord, value.as_ref()?))),
+        )
+    }
+}
+
+// Both 3-D grids get neighbours, rays, BFS and flood fill from `GridLike`, the same code the 2-D grids use. BFS
+// steps through the six faces, and `can_step` gets the `Direction3` it's stepping in.
+impl<T> GridLike for Grid3<T> {
+    type Cell = T;
+    type Pos = Coord3;
+    type Map<V> = Grid3<Option<V>>;
+    type Offset = (i64, i64, i64);
+    type Direction = Direction3;
+    type Connectivity = Connectivity3;
+
+    fn cell(&self, pos: &Coord3) -> Option<&T> {
+        self.get(pos)
+    }
+
+    fn step(&self, pos: &Coord3, offset: (i64, i64, i64)) -> Option<Coord3> {
+        pos.offset(offset)
+    }
+
+    fn new_map<V>(&self) -> Grid3<Option<V>> {
+        let (size_x, size_y, size_z) = self.size;
+        Grid3 {
+            size: self.size,
+            cells: (0..size_x * size_y * size_z).map(|_| None).collect(),
+        }
+    }
+}
+
+impl<T> GridLike for SparseGrid3<T> {
+    type Cell = T;
+    type Pos = Coord3;
+    type Map<V> = HashMap<Coord3, V>;
+    type Offset = (i64, i64, i64);
+    type Direction = Direction3;
+    type Connectivity = Connectivity3;
+
+    fn cell(&self, pos: &Coord3) -> Option<&T> {
+        if self.contains(pos) {
+            Some(self.get(pos))
+        } else {
+            None
+        }
+    }
+
+    fn step(&self, pos: &Coord3, offset: (i64, i64, i64)) -> Option<Coord3> {
+        pos.offset(offset)
+    }
+
+    fn new_map<V>(&self) -> HashMap<Coord3, V> {
+        HashMap::new()
+    }
+}
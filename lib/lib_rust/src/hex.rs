@@ -0,0 +1,308 @@
+use core::fmt;
+use std::collections::HashMap;
+use std::ops::{Add, Mul, Sub};
+
+use crate::grid::{Image, Rgb};
+
+// Hexagonal grids. Coordinates are axial (q, r), and the third cube coordinate s is always -q - r, so we only
+// store two of them. The maths all comes from https://www.redblobgames.com/grids/hexagons/, which is worth a read.
+//
+// The coordinates don't care which way up the hexagons are, that only matters when naming directions and drawing
+// them. `HexDirection` uses pointy-top names (e, ne, nw, w, sw, se). A flat-top grid is the same thing turned 30°
+// clockwise, so ne and sw mean the same on both, but a flat-top n is our NorthWest, nw is West, s is SouthEast
+// and se is East. `from_name` and `parse_moves` take care of that.
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Orientation {
+    PointyTop,
+    FlatTop,
+}
+
+// Going anticlockwise from East, so turning is just moving along the list
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, PartialOrd, Ord)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+impl HexDirection {
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::East,
+        HexDirection::NorthEast,
+        HexDirection::NorthWest,
+        HexDirection::West,
+        HexDirection::SouthWest,
+        HexDirection::SouthEast,
+    ];
+
+    pub fn offset(&self) -> Hex {
+        match self {
+            HexDirection::East => Hex::new(1, 0),
+            HexDirection::NorthEast => Hex::new(1, -1),
+            HexDirection::NorthWest => Hex::new(0, -1),
+            HexDirection::West => Hex::new(-1, 0),
+            HexDirection::SouthWest => Hex::new(-1, 1),
+            HexDirection::SouthEast => Hex::new(0, 1),
+        }
+    }
+
+    pub fn opposite(&self) -> HexDirection {
+        self.turn_left(3)
+    }
+
+    // `turns` is in steps of 60°
+    pub fn turn_left(&self, turns: usize) -> HexDirection {
+        HexDirection::ALL[(*self as usize + turns % 6) % 6]
+    }
+
+    pub fn turn_right(&self, turns: usize) -> HexDirection {
+        HexDirection::ALL[(*self as usize + 6 - turns % 6) % 6]
+    }
+
+    // Parses one direction name, using the names that make sense for `orientation`
+    pub fn from_name(name: &str, orientation: Orientation) -> Option<HexDirection> {
+        match (orientation, name) {
+            (_, "ne") => Some(HexDirection::NorthEast),
+            (_, "sw") => Some(HexDirection::SouthWest),
+            (Orientation::PointyTop, "e") => Some(HexDirection::East),
+            (Orientation::PointyTop, "nw") => Some(HexDirection::NorthWest),
+            (Orientation::PointyTop, "w") => Some(HexDirection::West),
+            (Orientation::PointyTop, "se") => Some(HexDirection::SouthEast),
+            (Orientation::FlatTop, "se") => Some(HexDirection::East),
+            (Orientation::FlatTop, "n") => Some(HexDirection::NorthWest),
+            (Orientation::FlatTop, "nw") => Some(HexDirection::West),
+            (Orientation::FlatTop, "s") => Some(HexDirection::SouthEast),
+            _ => None,
+        }
+    }
+
+    // Parses a string of moves. They can be run together like "esenee" or split up with commas or whitespace
+    // like "ne,ne,s". Panics on anything it doesn't recognise, since that means the input's been misread.
+    pub fn parse_moves(input: &str, orientation: Orientation) -> Vec<HexDirection> {
+        let mut moves = Vec::new();
+        let mut rest = input.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        while !rest.is_empty() {
+            let (dir, len) = [2, 1]
+                .into_iter()
+                .filter_map(|len| {
+                    let name = rest.get(..len)?;
+                    Some((HexDirection::from_name(name, orientation)?, len))
+                })
+                .next()
+                .unwrap_or_else(|| panic!("Bad hex move at {:?}", rest));
+            moves.push(dir);
+            rest = rest[len..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        }
+        moves
+    }
+}
+
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Default, PartialOrd, Ord)]
+pub struct Hex {
+    q: i64,
+    r: i64,
+}
+
+impl Hex {
+    pub const ORIGIN: Hex = Hex { q: 0, r: 0 };
+
+    pub fn new(q: i64, r: i64) -> Self {
+        Hex { q, r }
+    }
+
+    // Cube coordinates have to add up to zero, otherwise they aren't on the grid
+    pub fn from_cube(q: i64, r: i64, s: i64) -> Option<Self> {
+        if q + r + s == 0 {
+            Some(Hex { q, r })
+        } else {
+            None
+        }
+    }
+
+    pub fn q(&self) -> i64 {
+        self.q
+    }
+
+    pub fn r(&self) -> i64 {
+        self.r
+    }
+
+    pub fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    pub fn cube(&self) -> (i64, i64, i64) {
+        (self.q, self.r, self.s())
+    }
+
+    pub fn neighbour(&self, dir: &HexDirection) -> Hex {
+        *self + dir.offset()
+    }
+
+    pub fn neighbours(&self) -> impl Iterator<Item = Hex> + '_ {
+        HexDirection::ALL.iter().map(|dir| self.neighbour(dir))
+    }
+
+    // Where you end up after following every move in order
+    pub fn walk(&self, moves: &[HexDirection]) -> Hex {
+        moves.iter().fold(*self, |hex, dir| hex.neighbour(dir))
+    }
+
+    // The fewest steps between the two hexes
+    pub fn distance(&self, other: &Hex) -> u64 {
+        let (q, r, s) = (*self - *other).cube();
+        q.unsigned_abs().max(r.unsigned_abs()).max(s.unsigned_abs())
+    }
+
+    // Every hex exactly `radius` steps away, going anticlockwise and starting in the south west. A radius of
+    // zero is just this hex.
+    pub fn ring(&self, radius: u64) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![*self];
+        }
+        let mut hex = *self + HexDirection::SouthWest.offset() * radius as i64;
+        let mut ring = Vec::with_capacity(6 * radius as usize);
+        for dir in HexDirection::ALL {
+            for _ in 0..radius {
+                ring.push(hex);
+                hex = hex.neighbour(&dir);
+            }
+        }
+        ring
+    }
+
+    // Every hex up to `radius` steps away, ring by ring from the middle outwards
+    pub fn spiral(&self, radius: u64) -> impl Iterator<Item = Hex> + '_ {
+        (0..=radius).flat_map(|r| self.ring(r))
+    }
+
+    // Rotates about the origin in steps of 60°. Right is clockwise when drawn with y pointing down, like the
+    // image exporter does.
+    pub fn rotate_right(&self, turns: usize) -> Hex {
+        (0..turns % 6).fold(*self, |hex, _| {
+            let (q, r, s) = hex.cube();
+            Hex::from_cube(-r, -s, -q).unwrap()
+        })
+    }
+
+    pub fn rotate_left(&self, turns: usize) -> Hex {
+        self.rotate_right(6 - turns % 6)
+    }
+
+    pub fn rotate_around(&self, centre: &Hex, turns_right: usize) -> Hex {
+        (*self - *centre).rotate_right(turns_right) + *centre
+    }
+
+    // The centre of the hex in pixels, where `size` is the distance from the centre to a corner
+    pub fn to_pixel(&self, orientation: Orientation, size: f64) -> (f64, f64) {
+        let (q, r) = (self.q as f64, self.r as f64);
+        let sqrt3 = 3_f64.sqrt();
+        match orientation {
+            Orientation::PointyTop => (size * (sqrt3 * q + sqrt3 / 2.0 * r), size * 1.5 * r),
+            Orientation::FlatTop => (size * 1.5 * q, size * (sqrt3 / 2.0 * q + sqrt3 * r)),
+        }
+    }
+
+    // The hex that the pixel falls in
+    pub fn from_pixel(orientation: Orientation, size: f64, (x, y): (f64, f64)) -> Hex {
+        let sqrt3 = 3_f64.sqrt();
+        let (q, r) = match orientation {
+            Orientation::PointyTop => ((sqrt3 / 3.0 * x - y / 3.0) / size, (2.0 / 3.0 * y) / size),
+            Orientation::FlatTop => ((2.0 / 3.0 * x) / size, (-x / 3.0 + sqrt3 / 3.0 * y) / size),
+        };
+        Hex::round(q, r)
+    }
+
+    // Rounds fractional axial coordinates to the nearest hex. Rounding q and r on their own can land on the
+    // wrong hex, so we round all three cube coordinates and fix up the one that moved the most.
+    fn round(q: f64, r: f64) -> Hex {
+        let s = -q - r;
+        let (mut round_q, mut round_r, round_s) = (q.round(), r.round(), s.round());
+        let (d_q, d_r, d_s) = (
+            (round_q - q).abs(),
+            (round_r - r).abs(),
+            (round_s - s).abs(),
+        );
+        if d_q > d_r && d_q > d_s {
+            round_q = -round_r - round_s;
+        } else if d_r > d_s {
+            round_r = -round_q - round_s;
+        }
+        Hex::new(round_q as i64, round_r as i64)
+    }
+}
+
+impl fmt::Debug for Hex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.q, self.r)
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, other: Hex) -> Hex {
+        Hex::new(self.q + other.q, self.r + other.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+
+    fn sub(self, other: Hex) -> Hex {
+        Hex::new(self.q - other.q, self.r - other.r)
+    }
+}
+
+impl Mul<i64> for Hex {
+    type Output = Hex;
+
+    fn mul(self, n: i64) -> Hex {
+        Hex::new(self.q * n, self.r * n)
+    }
+}
+
+// Draws a map of hexes into an `Image`, `size` pixels from the centre of each hex to its corners. Anything not
+// in `cells` is left as `background`.
+pub fn to_image<T, P>(
+    cells: &HashMap<Hex, T>,
+    palette: P,
+    orientation: Orientation,
+    size: usize,
+    background: Rgb,
+) -> Image
+where
+    P: Fn(&T) -> Rgb,
+{
+    let size_f = size as f64;
+    let centres = cells
+        .keys()
+        .map(|hex| hex.to_pixel(orientation, size_f))
+        .collect::<Vec<_>>();
+    if centres.is_empty() {
+        return Image::new(0, 0, background);
+    }
+    let min_x = centres.iter().map(|(x, _)| *x).fold(f64::MAX, f64::min) - size_f;
+    let max_x = centres.iter().map(|(x, _)| *x).fold(f64::MIN, f64::max) + size_f;
+    let min_y = centres.iter().map(|(_, y)| *y).fold(f64::MAX, f64::min) - size_f;
+    let max_y = centres.iter().map(|(_, y)| *y).fold(f64::MIN, f64::max) + size_f;
+    let (width, height) = (
+        (max_x - min_x).ceil() as usize,
+        (max_y - min_y).ceil() as usize,
+    );
+
+    let mut image = Image::new(width, height, background);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = (min_x + x as f64 + 0.5, min_y + y as f64 + 0.5);
+            if let Some(item) = cells.get(&Hex::from_pixel(orientation, size_f, pixel)) {
+                image.set(x, y, palette(item));
+            }
+        }
+    }
+    image
+}
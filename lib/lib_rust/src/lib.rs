@@ -1 +1,3 @@
 pub mod grid;
+pub mod grid3;
+pub mod hex;